@@ -4,16 +4,55 @@ use {
     anyhow::{Result, anyhow},
     serde::Serialize,
     std::{
+        borrow::Cow,
+        collections::HashMap,
         fmt::Write,
         ops::{Index, Range, RangeBounds},
         slice::SliceIndex,
     },
+    unicode_normalization::{
+        UnicodeNormalization, char::is_combining_mark, is_nfc, is_nfd, is_nfkc, is_nfkd,
+    },
+    unicode_properties::{
+        EmojiStatus, GeneralCategoryGroup, UnicodeEmoji, UnicodeGeneralCategory,
+        emoji::is_regional_indicator,
+    },
     unicode_segmentation::{Graphemes, UnicodeSegmentation},
 };
 
+pub use unicode_properties::GeneralCategory;
+
+#[cfg(feature = "regex")]
+use regex::{Captures, Regex};
+
 //--------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Default, PartialEq, Serialize)]
+/**
+A single Unicode grapheme
+
+Graphemes are compared, ordered, and hashed by their [`char`]s, and implement [`Borrow<str>`] so
+collections keyed by [`Grapheme`] can be queried with a [`&str`]:
+
+```
+use {gstring::*, std::collections::HashMap};
+
+let mut counts = HashMap::new();
+for g in GString::from("e\u{301}ae\u{301}") {
+    *counts.entry(g).or_insert(0) += 1;
+}
+
+assert_eq!(counts["e\u{301}"], 2);
+assert_eq!(counts["a"], 1);
+assert_eq!(counts.get("e"), None);
+
+let mut g = graphemes("cba");
+g.sort();
+assert_eq!(g, &["a", "b", "c"]);
+```
+
+[`Borrow<str>`]: std::borrow::Borrow
+*/
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 pub struct Grapheme {
     data: String,
 }
@@ -100,1259 +139,6531 @@ impl Grapheme {
     pub fn as_str(&self) -> &str {
         &self.data
     }
-}
 
-impl std::fmt::Display for Grapheme {
     /**
-    Print a [`Grapheme`] directly in [`print`], [`println`], [`eprint`], [`eprintln`], and [`write`]
-    macros or convert to a [`String`] using the [`format`] macro [`to_string`][ToString::to_string]
-    method
+    Return [`true`] if the graphemes are canonically equivalent
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}";
-
-    let g = Grapheme::from(S).unwrap();
+    let precomposed = Grapheme::from("\u{e9}").unwrap();
+    let decomposed = Grapheme::from("e\u{301}").unwrap();
 
-    assert_eq!(format!("{g}"), S);
-    assert_eq!(format!("{}", g), S);
-    assert_eq!(g.to_string(), S);
+    assert_ne!(precomposed, decomposed);
+    assert!(precomposed.canonical_eq(&decomposed));
+    assert!(!precomposed.canonical_eq(&Grapheme::from("e").unwrap()));
     ```
+
+    Unlike [`PartialEq`], which compares the raw [`char`]s, this compares the canonical
+    decompositions (NFD).
     */
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        self.data.fmt(f)
+    #[must_use]
+    pub fn canonical_eq(&self, other: &Grapheme) -> bool {
+        self.data.nfd().eq(other.data.nfd())
     }
-}
 
-impl std::fmt::Debug for Grapheme {
     /**
-    Debug print a [`Grapheme`] in [`format`], [`print`], [`println`], [`write`], [`writeln`], etc
-    macros
+    Return the canonical composition (NFC)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}";
-
-    let s = GString::from(S);
+    let g = Grapheme::from("e\u{301}").unwrap();
 
-    assert_eq!(
-        format!("{:?}", s[0]),
-        format!("{:?}", S),
-    );
+    assert_eq!(g.nfc(), "\u{e9}");
+    assert!(g.nfc().is_nfc());
     ```
     */
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self.data)
+    #[must_use]
+    pub fn nfc(&self) -> Grapheme {
+        Grapheme {
+            data: self.data.nfc().collect(),
+        }
     }
-}
 
-impl std::cmp::PartialEq<&str> for Grapheme {
     /**
-    Compare a [`Grapheme`] to a [`&str`]
+    Return the canonical decomposition (NFD)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}";
-
-    let s = Grapheme::from(S).unwrap();
+    let g = Grapheme::from("\u{e9}").unwrap();
 
-    assert_eq!(s, S);
-    assert_ne!(s, "");
+    assert_eq!(g.nfd(), "e\u{301}");
+    assert!(g.nfd().is_nfd());
     ```
     */
-    fn eq(&self, other: &&str) -> bool {
-        self.data == *other
+    #[must_use]
+    pub fn nfd(&self) -> Grapheme {
+        Grapheme {
+            data: self.data.nfd().collect(),
+        }
     }
-}
 
-impl std::cmp::PartialEq<str> for Grapheme {
     /**
-    Compare a [`Grapheme`] to a [`str`]
+    Return the compatibility composition (NFKC)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}";
-
-    let s = Grapheme::from(S).unwrap();
+    let g = Grapheme::from("\u{fb01}").unwrap();
 
-    assert_eq!(s, S);
-    assert_ne!(s, "");
+    assert_eq!(g.nfkc(), "fi");
+    assert_eq!(g.nfkc().len(), 2);
     ```
+
+    Note that this returns a [`GString`] because compatibility mappings can expand a grapheme into
+    several graphemes.
     */
-    fn eq(&self, other: &str) -> bool {
-        self.data == other
+    #[must_use]
+    pub fn nfkc(&self) -> GString {
+        GString::from(&self.data.nfkc().collect::<String>())
     }
-}
-
-//--------------------------------------------------------------------------------------------------
 
-/// String with support for Unicode graphemes
-#[derive(Clone, Default, Serialize)]
-pub struct GString {
-    data: Vec<Grapheme>,
-    shape: Vec<usize>,
-}
-
-impl GString {
     /**
-    Create a new empty [`GString`]
+    Return the compatibility decomposition (NFKD)
 
     ```
     use gstring::*;
 
-    let s = GString::new();
+    let g = Grapheme::from("\u{1e9b}\u{323}").unwrap();
 
-    assert_eq!(s, "");
+    assert_eq!(g.nfkd(), "s\u{323}\u{307}");
     ```
+
+    Note that this returns a [`GString`] because compatibility mappings can expand a grapheme into
+    several graphemes.
     */
     #[must_use]
-    pub fn new() -> GString {
-        GString::default()
+    pub fn nfkd(&self) -> GString {
+        GString::from(&self.data.nfkd().collect::<String>())
     }
 
     /**
-    Create a new [`GString`] from a [`&str`]
+    Return [`true`] if the grapheme is in canonical composition (NFC)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-
-    let s = GString::from(S);
+    assert!(Grapheme::from("\u{e9}").unwrap().is_nfc());
+    assert!(!Grapheme::from("e\u{301}").unwrap().is_nfc());
 
-    assert_eq!(s, S);
+    // Compatibility characters are unchanged by NFC
+    assert!(Grapheme::from("\u{fb01}").unwrap().is_nfc());
     ```
     */
     #[must_use]
-    pub fn from(s: &str) -> GString {
-        let data = graphemes(s);
-        let shape = calc_shape(&data);
-        GString { data, shape }
+    pub fn is_nfc(&self) -> bool {
+        is_nfc(&self.data)
     }
 
     /**
-    Return a slice reference to the internal graphemes
+    Return [`true`] if the grapheme is in canonical decomposition (NFD)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-    const G: &[&str] = &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"];
-
-    let s = GString::from(S);
-    let g = s.graphemes();
+    assert!(Grapheme::from("e\u{301}").unwrap().is_nfd());
+    assert!(!Grapheme::from("\u{e9}").unwrap().is_nfd());
 
-    assert_eq!(g, G);
-    assert_eq!(g.len(), G.len());
+    // Combining marks must be in canonical order
+    assert!(Grapheme::from("o\u{332}\u{308}").unwrap().is_nfd());
+    assert!(!Grapheme::from("o\u{308}\u{332}").unwrap().is_nfd());
     ```
     */
     #[must_use]
-    pub fn graphemes(&self) -> &[Grapheme] {
-        &self.data
+    pub fn is_nfd(&self) -> bool {
+        is_nfd(&self.data)
     }
 
     /**
-    Consume the [`GString`] and convert to a [`Vec`] of graphemes
+    Return [`true`] if the grapheme is in compatibility composition (NFKC)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-    const G: &[&str] = &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"];
-
-    let g = GString::from(S).into_graphemes();
-
-    assert_eq!(g, G);
-    assert_eq!(g.len(), G.len());
+    assert!(Grapheme::from("\u{e9}").unwrap().is_nfkc());
+    assert!(!Grapheme::from("\u{fb01}").unwrap().is_nfkc());
+    assert!(!Grapheme::from("\u{b2}").unwrap().is_nfkc());
     ```
     */
     #[must_use]
-    pub fn into_graphemes(self) -> Vec<Grapheme> {
-        self.data
+    pub fn is_nfkc(&self) -> bool {
+        is_nfkc(&self.data)
     }
 
     /**
-    Returns the index of the first grapheme of this string slice that matches the pattern
+    Return [`true`] if the grapheme is in compatibility decomposition (NFKD)
 
     ```
     use gstring::*;
 
-    let g = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
-
-    assert_eq!(g.find(&GString::from("a\u{310}")), Some(0));
-    assert_eq!(g.find(&GString::from("e\u{301}")), Some(1));
-    assert_eq!(g.find(&GString::from("o\u{308}\u{332}")), Some(2));
-    assert!(g.find(&GString::from("nonexistent")).is_none());
+    assert!(Grapheme::from("e\u{301}").unwrap().is_nfkd());
+    assert!(!Grapheme::from("\u{e9}").unwrap().is_nfkd());
+    assert!(!Grapheme::from("\u{fb01}").unwrap().is_nfkd());
     ```
     */
     #[must_use]
-    pub fn find(&self, pattern: &GString) -> Option<usize> {
-        self.data
-            .as_slice()
-            .windows(pattern.len())
-            .enumerate()
-            .find(|(_, g)| g == &pattern.data)
-            .map(|(i, _)| i)
+    pub fn is_nfkd(&self) -> bool {
+        is_nfkd(&self.data)
     }
 
     /**
-    Returns the index of the first grapheme of this string slice that matches the pattern
+    Return [`true`] if all [`char`]s are whitespace
 
     ```
     use gstring::*;
 
-    let g = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
-
-    assert_eq!(g.find_str("a\u{310}"), Some(0));
-    assert_eq!(g.find_str("e\u{301}"), Some(1));
-    assert_eq!(g.find_str("o\u{308}\u{332}"), Some(2));
-    assert!(g.find_str("nonexistent").is_none());
+    assert!(Grapheme::from("\r\n").unwrap().is_whitespace());
+    assert!(Grapheme::from("\u{3000}").unwrap().is_whitespace());
+    assert!(!Grapheme::from(" \u{301}").unwrap().is_whitespace());
     ```
     */
     #[must_use]
-    pub fn find_str(&self, pattern: &str) -> Option<usize> {
-        self.find(&pattern.gstring())
+    pub fn is_whitespace(&self) -> bool {
+        self.data.chars().all(char::is_whitespace)
     }
 
     /**
-    Returns the index of the first grapheme of this string slice that matches the pattern after `n`
-    graphemes
+    Return [`true`] if the base [`char`] is alphabetic
 
     ```
     use gstring::*;
 
-    let g = GString::from("abc abc");
-
-    assert_eq!(g.find_from(0, &GString::from("abc")), Some(0));
-    assert_eq!(g.find_from(1, &GString::from("abc")), Some(4));
-    assert!(g.find_from(0, &GString::from("nonexistent")).is_none());
+    assert!(Grapheme::from("e\u{301}").unwrap().is_alphabetic());
+    assert!(Grapheme::from("\u{1100}\u{1161}").unwrap().is_alphabetic());
+    assert!(!Grapheme::from("1\u{20e3}").unwrap().is_alphabetic());
     ```
+
+    See [`Grapheme::base`].
     */
     #[must_use]
-    pub fn find_from(&self, n: usize, pattern: &GString) -> Option<usize> {
-        self.data[n..]
-            .windows(pattern.len())
-            .enumerate()
-            .find(|(_, g)| g == &pattern.data)
-            .map(|(i, _)| i + n)
+    pub fn is_alphabetic(&self) -> bool {
+        self.base().is_some_and(char::is_alphabetic)
     }
 
     /**
-    Returns the index of the first grapheme of this string slice that matches the pattern after `n`
-    graphemes
+    Return [`true`] if the base [`char`] is numeric
 
     ```
     use gstring::*;
 
-    let g = GString::from("abc abc");
-
-    assert_eq!(g.find_from_str(0, "abc"), Some(0));
-    assert_eq!(g.find_from_str(1, "abc"), Some(4));
-    assert!(g.find_from_str(0, "nonexistent").is_none());
+    assert!(Grapheme::from("1\u{20e3}").unwrap().is_numeric());
+    assert!(Grapheme::from("\u{661}").unwrap().is_numeric());
+    assert!(!Grapheme::from("a").unwrap().is_numeric());
     ```
+
+    See [`Grapheme::base`].
     */
     #[must_use]
-    pub fn find_from_str(&self, n: usize, pattern: &str) -> Option<usize> {
-        self.find_from(n, &pattern.gstring())
+    pub fn is_numeric(&self) -> bool {
+        self.base().is_some_and(char::is_numeric)
     }
 
     /**
-    Returns the index of the first grapheme of this string slice that matches the pattern before `n`
-    graphemes
+    Return [`true`] if the base [`char`] is alphabetic or numeric
 
     ```
     use gstring::*;
 
-    let g = GString::from("abc abc");
-
-    assert_eq!(g.find_prev_from(7, &GString::from("abc")), Some(4));
-    assert_eq!(g.find_prev_from(4, &GString::from("abc")), Some(0));
-    assert!(g.find_prev_from(7, &GString::from("nonexistent")).is_none());
+    assert!(Grapheme::from("a\u{310}").unwrap().is_alphanumeric());
+    assert!(Grapheme::from("7").unwrap().is_alphanumeric());
+    assert!(!Grapheme::from("_").unwrap().is_alphanumeric());
     ```
+
+    See [`Grapheme::base`].
     */
     #[must_use]
-    pub fn find_prev_from(&self, n: usize, pattern: &GString) -> Option<usize> {
-        let mut pattern = pattern.clone().into_graphemes();
-        pattern.reverse();
+    pub fn is_alphanumeric(&self) -> bool {
+        self.base().is_some_and(char::is_alphanumeric)
+    }
+
+    /**
+    Return [`true`] if the general category of the base [`char`] is punctuation
 
-        let length = self.len();
-        let n = length - n;
+    ```
+    use gstring::*;
 
-        let mut data = self.data.clone();
-        data.reverse();
+    assert!(Grapheme::from("!").unwrap().is_punctuation());
+    assert!(Grapheme::from("\u{ab}").unwrap().is_punctuation());
+    assert!(Grapheme::from("\u{3002}").unwrap().is_punctuation());
+    assert!(!Grapheme::from("+").unwrap().is_punctuation());
+    ```
 
-        data[n..]
-            .windows(pattern.len())
-            .enumerate()
-            .find(|(_, g)| g == &pattern)
-            .map(|(i, _)| length - i - n - pattern.len())
+    See [`Grapheme::base`].
+    */
+    #[must_use]
+    pub fn is_punctuation(&self) -> bool {
+        self.base()
+            .is_some_and(|c| c.general_category_group() == GeneralCategoryGroup::Punctuation)
     }
 
     /**
-    Returns the index of the first grapheme of this string slice that matches the pattern before `n`
-    graphemes
+    Return [`true`] if the base [`char`] is a control character
 
     ```
     use gstring::*;
 
-    let g = GString::from("abc abc");
-
-    assert_eq!(g.find_prev_from_str(7, "abc"), Some(4));
-    assert_eq!(g.find_prev_from_str(4, "abc"), Some(0));
-    assert!(g.find_prev_from_str(7, "nonexistent").is_none());
+    assert!(Grapheme::from("\r\n").unwrap().is_control());
+    assert!(Grapheme::from("\t").unwrap().is_control());
+    assert!(!Grapheme::from("\u{200d}").unwrap().is_control());
     ```
+
+    See [`Grapheme::base`].
     */
     #[must_use]
-    pub fn find_prev_from_str(&self, n: usize, pattern: &str) -> Option<usize> {
-        self.find_prev_from(n, &pattern.gstring())
+    pub fn is_control(&self) -> bool {
+        self.base().is_some_and(char::is_control)
     }
 
     /**
-    Return a reference to the grapheme at `index`
+    Return [`true`] if the grapheme is displayed as an emoji
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    assert!(Grapheme::from("\u{1f600}").unwrap().is_emoji());
+    assert!(Grapheme::from("\u{1f469}\u{200d}\u{1f4bb}").unwrap().is_emoji());
+    assert!(Grapheme::from("\u{1f1fa}\u{1f1f8}").unwrap().is_emoji());
+    assert!(Grapheme::from("\u{2764}\u{fe0f}").unwrap().is_emoji());
+    assert!(!Grapheme::from("\u{2764}").unwrap().is_emoji());
+    assert!(!Grapheme::from("1").unwrap().is_emoji());
+    ```
 
-    let g = GString::from(S);
+    Characters with text presentation by default, like U+2764 HEAVY BLACK HEART, are emoji only
+    when followed by an emoji presentation selector, keycap or zero width joiner.
+    */
+    #[must_use]
+    pub fn is_emoji(&self) -> bool {
+        is_emoji(&self.data)
+    }
+
+    /**
+    Return [`true`] if all [`char`]s are ASCII
 
-    assert_eq!(g.get(0).unwrap(), "a\u{310}");
-    assert_eq!(g.get(1).unwrap(), "e\u{301}");
-    assert_eq!(g.get(2).unwrap(), "o\u{308}\u{332}");
-    assert!(g.get(3).is_none());
+    ```
+    use gstring::*;
+
+    assert!(Grapheme::from("\r\n").unwrap().is_ascii());
+    assert!(!Grapheme::from("e\u{301}").unwrap().is_ascii());
     ```
     */
     #[must_use]
-    pub fn get(&self, index: usize) -> Option<&Grapheme> {
-        (index < self.len()).then(|| &self.data[index])
+    pub fn is_ascii(&self) -> bool {
+        self.data.is_ascii()
     }
 
     /**
-    Return the count of graphemes
+    Return the general category of the base [`char`]
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    assert_eq!(
+        Grapheme::from("e\u{301}").unwrap().general_category(),
+        GeneralCategory::LowercaseLetter,
+    );
+    assert_eq!(
+        Grapheme::from("\u{1f600}").unwrap().general_category(),
+        GeneralCategory::OtherSymbol,
+    );
+    assert_eq!(
+        Grapheme::from("\r\n").unwrap().general_category(),
+        GeneralCategory::Control,
+    );
+    ```
 
-    let s = GString::from(S);
-    assert_eq!(s.len(), 3);
+    See [`Grapheme::base`].
+    */
+    #[must_use]
+    pub fn general_category(&self) -> GeneralCategory {
+        self.base().map_or(
+            GeneralCategory::Unassigned,
+            UnicodeGeneralCategory::general_category,
+        )
+    }
 
-    let s = GString::from("");
-    assert_eq!(s.len(), 0);
+    /**
+    Return the base [`char`]
+
+    ```
+    use gstring::*;
+
+    assert_eq!(Grapheme::from("e\u{301}").unwrap().base(), Some('e'));
+    assert_eq!(Grapheme::from("\u{e9}").unwrap().base(), Some('\u{e9}'));
+    assert_eq!(Grapheme::from("\u{e9}").unwrap().nfd().base(), Some('e'));
+    assert_eq!(Grapheme::default().base(), None);
     ```
+
+    The base [`char`] is the first [`char`] of the grapheme as stored, so precomposed characters are
+    not decomposed unless [`Grapheme::nfd`] is used first.
+    Returns [`None`] only for the empty [`Grapheme::default`].
     */
     #[must_use]
-    pub fn len(&self) -> usize {
-        self.data.len()
+    pub fn base(&self) -> Option<char> {
+        self.data.chars().next()
     }
 
     /**
-    Return [`true`] if the [`GString`] has zero graphemes otherwise return [`false`]
+    Return a [`Vec`] of the combining marks
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-
-    let s = GString::from("");
-    assert!(s.is_empty());
+    let g = Grapheme::from("o\u{308}\u{332}").unwrap();
 
-    let s = GString::from(S);
-    assert!(!s.is_empty());
+    // In canonical order: U+332 (below) sorts before U+308 (above)
+    assert_eq!(g.marks(), &['\u{332}', '\u{308}']);
+    assert_eq!(Grapheme::from("\u{f6}").unwrap().marks(), &['\u{308}']);
+    assert!(Grapheme::from("o").unwrap().marks().is_empty());
     ```
+
+    The grapheme is decomposed ([`Grapheme::nfd`]) first, so precomposed characters yield their
+    marks, in canonical order.
+    Combining marks are the [`char`]s in the Mark general categories (Mn, Mc and Me), which includes
+    variation selectors and the combining enclosing keycap.
     */
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+    pub fn marks(&self) -> Vec<char> {
+        self.data.nfd().filter(|c| is_combining_mark(*c)).collect()
     }
 
     /**
-    Return a [`Vec`] of [`char`]s
+    Return a new [`GString`] without the combining marks
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-    const C: &[char] = &['a', '\u{310}', 'e', '\u{301}', 'o', '\u{308}', '\u{332}'];
+    let g = Grapheme::from("o\u{308}\u{332}").unwrap();
 
-    let c = GString::from(S).chars();
+    assert_eq!(g.strip_marks(), "o");
+    assert_eq!(Grapheme::from("\u{f6}").unwrap().strip_marks(), "o");
+    assert_eq!(Grapheme::from("\u{1e69}").unwrap().strip_marks(), "s");
+    assert!(Grapheme::from("\u{301}").unwrap().strip_marks().is_empty());
 
-    assert_eq!(c, C);
-    assert_eq!(c.len(), C.len());
+    // Hangul syllables have no marks, so they are recomposed unchanged
+    assert_eq!(Grapheme::from("\u{ac00}").unwrap().strip_marks(), "\u{ac00}");
     ```
+
+    The grapheme is decomposed ([`Grapheme::nfd`]) before the marks are removed and the rest is
+    recomposed ([`Grapheme::nfc`]).
+    Note that this returns a [`GString`] because a grapheme consisting only of combining marks
+    becomes empty.
+    See [`Grapheme::marks`].
     */
     #[must_use]
-    pub fn chars(&self) -> Vec<char> {
-        self.data.iter().flat_map(Grapheme::chars).collect()
+    pub fn strip_marks(&self) -> GString {
+        GString::from(
+            &self
+                .data
+                .nfd()
+                .filter(|c| !is_combining_mark(*c))
+                .nfc()
+                .collect::<String>(),
+        )
     }
 
     /**
-    Return a [`Vec`] of [`u8`]s
+    Return a [`Vec`] of the components joined by zero width joiners (U+200D)
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-    const B: &[u8] = &[0x61, 0xcc, 0x90, 0x65, 0xcc, 0x81, 0x6f, 0xcc, 0x88, 0xcc, 0xb2];
-
-    let b = GString::from(S).bytes();
+    let g = Grapheme::from("\u{1f469}\u{1f3fd}\u{200d}\u{1f4bb}").unwrap();
 
-    assert_eq!(b, B);
-    assert_eq!(b.len(), B.len());
+    assert_eq!(g.zwj_components(), &["\u{1f469}\u{1f3fd}", "\u{1f4bb}"]);
+    assert_eq!(Grapheme::from("\u{1f600}").unwrap().zwj_components(), &["\u{1f600}"]);
     ```
     */
     #[must_use]
-    pub fn bytes(&self) -> Vec<u8> {
-        self.data.iter().flat_map(Grapheme::bytes).collect()
+    pub fn zwj_components(&self) -> Vec<Grapheme> {
+        self.data
+            .split('\u{200d}')
+            .filter(|s| !s.is_empty())
+            .map(|s| Grapheme {
+                data: s.to_string(),
+            })
+            .collect()
     }
 
     /**
-    Split into lines as a [`Vec`] of [`GString`]s
+    Return a [`Vec`] of the emoji skin tone modifiers
 
     ```
     use gstring::*;
 
-    assert_eq!(GString::from("abc\ndef").lines(), &["abc\n", "def"]);
-    assert_eq!(GString::from("abc\n").lines(), &["abc\n", ""]);
-    assert_eq!(GString::from("\ndef").lines(), &["\n", "def"]);
-    ```
+    let g = Grapheme::from("\u{1f44d}\u{1f3fd}").unwrap();
 
-    Note that unlike [`str::lines`], this method includes the original newline graphemes at the end
-    of each line.
+    assert_eq!(g.skin_tones(), &[SkinTone::Medium]);
+
+    let g = Grapheme::from(
+        "\u{1f9d1}\u{1f3fb}\u{200d}\u{1f91d}\u{200d}\u{1f9d1}\u{1f3ff}",
+    )
+    .unwrap();
+
+    assert_eq!(g.skin_tones(), &[SkinTone::Light, SkinTone::Dark]);
+    ```
     */
     #[must_use]
-    pub fn lines(&self) -> Vec<GString> {
-        lines(&self.data)
+    pub fn skin_tones(&self) -> Vec<SkinTone> {
+        self.data.chars().filter_map(SkinTone::from_char).collect()
     }
 
     /**
-    Return the coordinates `(row, column)` for a given position
+    Return a [`Vec`] of the variation selectors
 
     ```
     use gstring::*;
 
-    let g = GString::from("abc\ndef");
+    assert_eq!(Grapheme::from("\u{2764}\u{fe0f}").unwrap().variation_selectors(), &['\u{fe0f}']);
+    assert_eq!(Grapheme::from("\u{2764}\u{fe0e}").unwrap().variation_selectors(), &['\u{fe0e}']);
+    assert!(Grapheme::from("\u{2764}").unwrap().variation_selectors().is_empty());
+    ```
 
-    assert_eq!(g.coordinates(0), Some((0, 0)));
-    assert_eq!(g.coordinates(1), Some((0, 1)));
-    assert_eq!(g.coordinates(2), Some((0, 2)));
-    assert_eq!(g.coordinates(3), Some((0, 3)));
-    assert_eq!(g.coordinates(4), Some((1, 0)));
-    assert_eq!(g.coordinates(5), Some((1, 1)));
-    assert_eq!(g.coordinates(6), Some((1, 2)));
-    assert_eq!(g.coordinates(7), Some((1, 3)));
-    assert_eq!(g.coordinates(8), None);
-    ```
-
-    Note that newlines are located at the end of each line.
-    Also a valid coordinate exists at a [`GString`]'s position equal to its length, however no valid
-    coordinate exists for any greater position.
-
-    See also the [`GString::position`] method.
+    Includes the standard (U+FE00..U+FE0F) and supplementary (U+E0100..U+E01EF) variation
+    selectors.
     */
-    #[allow(clippy::missing_panics_doc)]
     #[must_use]
-    pub fn coordinates(&self, position: usize) -> Option<(usize, usize)> {
-        (position <= self.len()).then(|| {
-            let n = newline_indices(&self.data[..position]);
-            let row = n.len();
-            let column = if row == 0 {
-                position
-            } else {
-                position - n.last().unwrap() - 1
-            };
-            (row, column)
-        })
+    pub fn variation_selectors(&self) -> Vec<char> {
+        self.data
+            .chars()
+            .filter(|c| matches!(c, '\u{fe00}'..='\u{fe0f}' | '\u{e0100}'..='\u{e01ef}'))
+            .collect()
     }
 
     /**
-    Return the position for given coordinates `(row, column)`
+    Return the pair of regional indicators of a flag emoji
 
     ```
     use gstring::*;
 
-    /*
-      0 1 2 3   column
-    0 a b c \n
-      0 1 2 3   position
-
-      0 1 2 3   column
-    1 d e f
-      4 5 6 7   position
-    */
-
-    let g = GString::from("abc\ndef");
-
-    assert_eq!(g.position((0, 0)), Some(0));
-    assert_eq!(g.position((0, 1)), Some(1));
-    assert_eq!(g.position((0, 2)), Some(2));
-    assert_eq!(g.position((0, 3)), Some(3));
-    assert_eq!(g.position((0, 4)), None);
-    assert_eq!(g.position((1, 0)), Some(4));
-    assert_eq!(g.position((1, 1)), Some(5));
-    assert_eq!(g.position((1, 2)), Some(6));
-    assert_eq!(g.position((1, 3)), Some(7));
-    assert_eq!(g.position((1, 4)), None);
-    assert_eq!(g.position((2, 0)), None);
-
-    let g = GString::from("");
+    let g = Grapheme::from("\u{1f1fa}\u{1f1f8}").unwrap();
 
-    assert_eq!(g.position((0, 0)), Some(0));
-    assert_eq!(g.position((0, 1)), None);
-    assert_eq!(g.position((1, 0)), None);
+    assert_eq!(g.regional_indicators(), Some(['\u{1f1fa}', '\u{1f1f8}']));
+    assert_eq!(Grapheme::from("\u{1f1fa}").unwrap().regional_indicators(), None);
+    assert_eq!(Grapheme::from("U").unwrap().regional_indicators(), None);
     ```
 
-    Note that newlines are located at the end of each line.
-    Also a valid coordinate exists at a [`GString`]'s position equal to its length, however no valid
-    coordinate exists for any greater position.
-
-    See also the [`GString::coordinates`] method.
+    Returns [`None`] unless the grapheme consists of exactly 2 regional indicators.
     */
     #[must_use]
-    pub fn position(&self, coordinates: (usize, usize)) -> Option<usize> {
-        if self.is_empty() {
-            // Empty, so only valid coordinate is `(0, 0)` and position is `0`
-            (coordinates == (0, 0)).then_some(0)
-        } else {
-            // Not empty...
-            match coordinates {
-                (0, 0) => {
-                    // Shortcut `(0, 0)` to `0`
-                    Some(0)
-                }
-                (row, column) => {
-                    // Not `(0, 0)`...
-                    let newlines = self.newlines();
-                    let last_row = newlines.len();
-                    if row <= last_row {
-                        // Valid row
-                        let lines = self.lines();
-                        let last_column = lines[row].len();
-                        if row == last_row && column == last_column {
-                            // Last row and last column
-                            Some(self.len())
-                        } else if column < last_column {
-                            // Valid column
-                            // Sum lengths of prior lines and add the column
-                            Some(lines[..row].iter().map(GString::len).sum::<usize>() + column)
-                        } else {
-                            // Invalid column
-                            None
-                        }
-                    } else {
-                        // Invalid row
-                        None
-                    }
-                }
+    pub fn regional_indicators(&self) -> Option<[char; 2]> {
+        let mut chars = self.data.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(a), Some(b), None) if is_regional_indicator(a) && is_regional_indicator(b) => {
+                Some([a, b])
             }
+            _ => None,
         }
     }
 
     /**
-    Return the indices of all newlines
+    Return the ISO 3166-1 alpha-2 country code of a flag emoji
 
     ```
     use gstring::*;
 
-    assert_eq!(GString::from("abc\ndef").newlines(), &[3]);
-    assert_eq!(GString::from("abc\ndef\n").newlines(), &[3, 7]);
-    assert_eq!(GString::from("abc").newlines(), &[]);
-    assert_eq!(GString::from("").newlines(), &[]);
-    assert_eq!(GString::from("\n").newlines(), &[0]);
-    assert_eq!(GString::from("\n\n").newlines(), &[0, 1]);
+    assert_eq!(Grapheme::from("\u{1f1fa}\u{1f1f8}").unwrap().country_code().unwrap(), "US");
+    assert_eq!(Grapheme::from("\u{1f1ef}\u{1f1f5}").unwrap().country_code().unwrap(), "JP");
+    assert_eq!(Grapheme::from("\u{1f600}").unwrap().country_code(), None);
     ```
+
+    The letters are converted without checking that the code is assigned.
+    See [`Grapheme::regional_indicators`].
     */
     #[must_use]
-    pub fn newlines(&self) -> Vec<usize> {
-        newline_indices(&self.data)
+    pub fn country_code(&self) -> Option<String> {
+        self.regional_indicators().map(|pair| {
+            pair.iter()
+                .filter_map(|c| char::from_u32(u32::from(*c) - 0x1f1e6 + u32::from(b'A')))
+                .collect()
+        })
     }
+}
 
+impl std::fmt::Display for Grapheme {
     /**
-    Insert a string at an index
+    Print a [`Grapheme`] directly in [`print`], [`println`], [`eprint`], [`eprintln`], and [`write`]
+    macros or convert to a [`String`] using the [`format`] macro [`to_string`][ToString::to_string]
+    method
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const S: &str = "a\u{310}";
 
-    let mut s = GString::from("a\u{310}o\u{308}\u{332}");
-    s.insert(1, "e\u{301}");
+    let g = Grapheme::from(S).unwrap();
 
-    assert_eq!(s, S);
+    assert_eq!(format!("{g}"), S);
+    assert_eq!(format!("{}", g), S);
+    assert_eq!(g.to_string(), S);
     ```
     */
-    pub fn insert(&mut self, index: usize, string: &str) {
-        let _ = self.splice(index..index, string);
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.data.fmt(f)
     }
+}
 
+impl std::fmt::Debug for Grapheme {
     /**
-    Remove a grapheme at an index
+    Debug print a [`Grapheme`] in [`format`], [`print`], [`println`], [`write`], [`writeln`], etc
+    macros
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const S: &str = "a\u{310}";
 
-    let mut s = GString::from(S);
+    let s = GString::from(S);
 
-    assert_eq!(s.remove(1), "e\u{301}");
-    assert_eq!(s, "a\u{310}o\u{308}\u{332}");
+    assert_eq!(
+        format!("{:?}", s[0]),
+        format!("{:?}", S),
+    );
     ```
     */
-    pub fn remove(&mut self, index: usize) -> Grapheme {
-        let r = self.data.remove(index);
-        self.shape = calc_shape(&self.data);
-        r
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self.data)
     }
+}
 
+impl std::cmp::PartialEq<&str> for Grapheme {
     /**
-    Append a [`&str`]
+    Compare a [`Grapheme`] to a [`&str`]
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const S: &str = "a\u{310}";
 
-    let mut s = GString::from("a\u{310}e\u{301}");
-    s.push("o\u{308}\u{332}");
+    let s = Grapheme::from(S).unwrap();
 
     assert_eq!(s, S);
+    assert_ne!(s, "");
     ```
     */
-    pub fn push(&mut self, string: &str) {
-        self.data.append(&mut graphemes(string));
-        self.shape = calc_shape(&self.data);
+    fn eq(&self, other: &&str) -> bool {
+        self.data == *other
     }
+}
 
+impl std::cmp::PartialEq<str> for Grapheme {
     /**
-    Remove the last grapheme and return it as a new [`GString`]
+    Compare a [`Grapheme`] to a [`str`]
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-
-    let mut s = GString::from(S);
-
-    assert_eq!(s.pop().unwrap(), "o\u{308}\u{332}");
-    assert_eq!(s, "a\u{310}e\u{301}");
-
-    assert_eq!(s.pop().unwrap(), "e\u{301}");
-    assert_eq!(s, "a\u{310}");
+    const S: &str = "a\u{310}";
 
-    assert_eq!(s.pop().unwrap(), "a\u{310}");
-    assert_eq!(s, "");
+    let s = Grapheme::from(S).unwrap();
 
-    assert_eq!(s.pop(), None);
-    assert_eq!(s, "");
+    assert_eq!(s, S);
+    assert_ne!(s, "");
     ```
     */
-    pub fn pop(&mut self) -> Option<Grapheme> {
-        self.data.pop()
+    fn eq(&self, other: &str) -> bool {
+        self.data == other
     }
+}
 
-    /**
-    Replace a range with a [`&str`]
+impl std::borrow::Borrow<str> for Grapheme {
+    /// Borrow a [`Grapheme`] as a [`str`]
+    fn borrow(&self) -> &str {
+        &self.data
+    }
+}
 
-    The range can be a `a..b` [`Range<usize>`], `a..` [`RangeFrom<usize>`], `..b`
-    [`RangeTo<usize>`], or `..` [`RangeFull`].
+impl From<Grapheme> for String {
+    /**
+    Convert a [`Grapheme`] into a [`String`]
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    let s: String = Grapheme::from("a\u{310}").unwrap().into();
 
-    let mut s = GString::from(S);
+    assert_eq!(s, "a\u{310}");
+    ```
+    */
+    fn from(g: Grapheme) -> String {
+        g.data
+    }
+}
 
-    assert_eq!(s.splice(0..2, "e\u{301}a\u{310}"), "a\u{310}e\u{301}");
-    assert_eq!(s, "e\u{301}a\u{310}o\u{308}\u{332}");
+//--------------------------------------------------------------------------------------------------
 
-    assert_eq!(s.splice(1.., "o\u{308}\u{332}a\u{310}"), "a\u{310}o\u{308}\u{332}");
-    assert_eq!(s, "e\u{301}o\u{308}\u{332}a\u{310}");
+/**
+String with support for Unicode graphemes
 
-    assert_eq!(s.splice(..1, ""), "e\u{301}");
-    assert_eq!(s, "o\u{308}\u{332}a\u{310}");
+[`GString`]s are compared, ordered, and hashed by their [`char`]s (codepoints), like [`String`]s,
+regardless of how the [`char`]s are segmented into graphemes.
+[`GString`] does not store a contiguous [`str`], so it cannot implement
+[`Borrow<str>`][std::borrow::Borrow], and collections keyed by [`GString`] are queried with a
+[`GString`]:
 
-    assert_eq!(s.splice(.., ""), "o\u{308}\u{332}a\u{310}");
-    assert_eq!(s, "");
-    ```
+```
+use {gstring::*, std::collections::HashSet};
 
-    [`RangeFrom<usize>`]: std::ops::RangeFrom
+let set = HashSet::from([GString::from("e\u{301}")]);
 
-    [`RangeTo<usize>`]: std::ops::RangeTo
+assert!(set.contains(&GString::from("e\u{301}")));
+```
+*/
+#[derive(Clone, Serialize)]
+pub struct GString {
+    data: Vec<Grapheme>,
+    shape: Vec<usize>,
+    #[serde(skip)]
+    newline_policy: NewlinePolicy,
+}
 
-    [`RangeFull`]: std::ops::RangeFull
+impl GString {
+    /**
+    Create a new empty [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::new();
+
+    assert_eq!(s, "");
+    assert_eq!(s.shape(), GString::from("").shape());
+    ```
     */
     #[must_use]
-    pub fn splice<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &str) -> GString {
-        let data = self
-            .data
-            .splice(range, graphemes(replace_with))
-            .collect::<Vec<_>>();
-        let shape = calc_shape(&data);
-        self.shape = calc_shape(&self.data);
-        GString { data, shape }
+    pub fn new() -> GString {
+        GString::default()
     }
 
     /**
-    Remove and return a range of graphemes
-
-    The range can be a `a..b` [`Range<usize>`], `a..` [`RangeFrom<usize>`], `..b`
-    [`RangeTo<usize>`], or `..` [`RangeFull`].
+    Return a slice reference to the internal graphemes
 
     ```
     use gstring::*;
 
-    let mut s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}a\u{310}e\u{301}");
-
-    assert_eq!(s.drain(0..2), "a\u{310}e\u{301}");
-    assert_eq!(s, "o\u{308}\u{332}a\u{310}e\u{301}");
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const G: &[&str] = &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"];
 
-    assert_eq!(s.drain(2..), "e\u{301}");
-    assert_eq!(s, "o\u{308}\u{332}a\u{310}");
+    let s = GString::from(S);
+    let g = s.graphemes();
 
-    assert_eq!(s.drain(..1), "o\u{308}\u{332}");
-    assert_eq!(s, "a\u{310}");
-
-    assert_eq!(s.drain(..), "a\u{310}");
-    assert_eq!(s, "");
+    assert_eq!(g, G);
+    assert_eq!(g.len(), G.len());
     ```
-
-    [`RangeFrom<usize>`]: std::ops::RangeFrom
-
-    [`RangeTo<usize>`]: std::ops::RangeTo
-
-    [`RangeFull`]: std::ops::RangeFull
     */
     #[must_use]
-    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> GString {
-        let data = self.data.drain(range).collect::<Vec<_>>();
-        let shape = calc_shape(&data);
-        self.shape = calc_shape(&self.data);
-        GString { data, shape }
+    pub fn graphemes(&self) -> &[Grapheme] {
+        &self.data
     }
 
     /**
-    Create a new [`GString`] from an `a..b` [`Range<usize>`]
+    Consume the [`GString`] and convert to a [`Vec`] of graphemes
 
     ```
     use gstring::*;
 
     const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const G: &[&str] = &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"];
 
-    let s = GString::from(S);
+    let g = GString::from(S).into_graphemes();
 
-    assert_eq!(s.slice(0..1), "a\u{310}");
-    assert_eq!(s.slice(1..2), "e\u{301}");
-    assert_eq!(s.slice(2..3), "o\u{308}\u{332}");
-    assert_eq!(s.slice(0..2), "a\u{310}e\u{301}");
-    assert_eq!(s.slice(1..3), "e\u{301}o\u{308}\u{332}");
-    assert_eq!(s.slice(0..3), S);
+    assert_eq!(g, G);
+    assert_eq!(g.len(), G.len());
     ```
-
-    See also the [`GString::index`] method.
     */
     #[must_use]
-    pub fn slice(&self, range: Range<usize>) -> GString {
-        let data = self.data[range].to_vec();
-        let shape = calc_shape(&data);
-        GString { data, shape }
+    pub fn into_graphemes(self) -> Vec<Grapheme> {
+        self.data
     }
 
     /**
-    Return a reference to the "shape" of the content
-
-    - Length of the shape: Number of lines
-    - Values: Maximum column index for each line (including the newline)
-
-    | Line    | Count | Row Index | Max Column Index |
-    |---------|------:|----------:|-----------------:|
-    | `\n`    |     1 |         0 |                0 |
-    | `a\n`   |     2 |         1 |                1 |
-    | `bc\n`  |     3 |         2 |                2 |
-    | `d\n`   |     4 |         3 |                1 |
-    | `efg\n` |     5 |         4 |                3 |
-    | `\n`    |     6 |         5 |                0 |
+    Returns the index of the first grapheme of this string slice that matches the pattern
 
     ```
     use gstring::*;
 
-    let s = GString::from("\na\nbc\nd\nefg\n");
+    let g = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
 
-    let shape = s.shape();
+    assert_eq!(g.find(&GString::from("a\u{310}")), Some(0));
+    assert_eq!(g.find(&GString::from("e\u{301}")), Some(1));
+    assert_eq!(g.find(&GString::from("o\u{308}\u{332}")), Some(2));
+    assert!(g.find(&GString::from("nonexistent")).is_none());
+    assert_eq!(g.find(&GString::new()), Some(0));
 
-    assert_eq!(shape, &[0, 1, 2, 1, 3, 0]);
+    assert_eq!(g.find("e\u{301}"), Some(1));
+    assert_eq!(g.find(|g: &Grapheme| g.chars().len() == 3), Some(2));
+    assert_eq!(g.find('a'), None);
+    ```
 
-    // There are 6 lines
-    assert_eq!(shape.len(), 6);
+    Uses the Boyer-Moore-Horspool algorithm over graphemes for sequence patterns.
 
-    // Max column index of the 5th line is 3
-    assert_eq!(shape[4], 3);
-    ```
+    See also the [`GPattern`] trait.
     */
     #[must_use]
-    pub fn shape(&self) -> &[usize] {
-        &self.shape
+    pub fn find<P: GPattern>(&self, pattern: P) -> Option<usize> {
+        pattern.searcher().find_in(&self.data).map(|r| r.start)
     }
 
     /**
-    Generate string showing the row, column, and position for each grapheme
+    Returns the index of the first grapheme of this string slice that matches the pattern
 
     ```
     use gstring::*;
 
-    let s = GString::from("a\nbc\ndef\nghij");
+    let g = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
 
-    let d = "  \
-      0 1 2 3 4
+    assert_eq!(g.find_str("a\u{310}"), Some(0));
+    assert_eq!(g.find_str("e\u{301}"), Some(1));
+    assert_eq!(g.find_str("o\u{308}\u{332}"), Some(2));
+    assert!(g.find_str("nonexistent").is_none());
+    ```
+    */
+    #[deprecated(
+        since = "0.14.0",
+        note = "use `GString::find` which accepts any `GPattern`"
+    )]
+    #[must_use]
+    pub fn find_str(&self, pattern: &str) -> Option<usize> {
+        self.find(pattern)
+    }
 
-      0 1
-    0 a \\n
-      0 1
+    /**
+    Returns the index of the first grapheme of this string slice that matches the pattern after `n`
+    graphemes
 
-      0 1 2
-    1 b c \\n
-      2 3 4
+    ```
+    use gstring::*;
 
-      0 1 2 3
-    2 d e f \\n
-      5 6 7 8
+    let g = GString::from("abc abc");
 
-      0 1 2 3 4
-    3 g h i j
-      0 1 1 1 1
-      9 0 1 2 3
+    assert_eq!(g.find_from(0, &GString::from("abc")), Some(0));
+    assert_eq!(g.find_from(1, &GString::from("abc")), Some(4));
+    assert!(g.find_from(0, &GString::from("nonexistent")).is_none());
+    assert_eq!(g.find_from(1, "abc"), Some(4));
+    ```
 
-    ";
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn find_from<P: GPattern>(&self, n: usize, pattern: P) -> Option<usize> {
+        pattern
+            .searcher()
+            .find_in(&self.data[n..])
+            .map(|r| r.start + n)
+    }
+
+    /**
+    Returns the index of the first grapheme of this string slice that matches the pattern after `n`
+    graphemes
 
-    assert_eq!(s.shape_string(), d);
     ```
+    use gstring::*;
 
-    # Notes
+    let g = GString::from("abc abc");
 
-    1. There is a column header at the top to show the column header for the longest row.
-    2. Each content row has:
-        - Row column header above
-        - Row index to the left
-        - Position (offset) below
-    3. The last row shows the column and position one grapheme past the end.
+    assert_eq!(g.find_from_str(0, "abc"), Some(0));
+    assert_eq!(g.find_from_str(1, "abc"), Some(4));
+    assert!(g.find_from_str(0, "nonexistent").is_none());
+    ```
     */
-    #[allow(clippy::missing_panics_doc)]
+    #[deprecated(
+        since = "0.14.0",
+        note = "use `GString::find_from` which accepts any `GPattern`"
+    )]
     #[must_use]
-    pub fn shape_string(&self) -> String {
-        let mut r = String::new();
+    pub fn find_from_str(&self, n: usize, pattern: &str) -> Option<usize> {
+        self.find_from(n, pattern)
+    }
 
-        // Column header at top
-        let max_column = *self
-            .shape
-            .iter()
-            .max()
-            .unwrap()
-            .max(&(*self.shape.last().unwrap() + 1));
-        let last_row = self.shape.len() - 1;
-        let row_width = n_digits(last_row);
-        let row_space = " ".repeat(row_width);
-        let e = n_digits(max_column);
-        for n in 0..e {
-            writeln!(
-                r,
-                "{row_space} {}",
-                (0..=max_column)
-                    .map(|x| { format!("{x:0e$}").chars().nth(n).unwrap().to_string() })
-                    .collect::<Vec<_>>()
-                    .join(" "),
-            )
-            .unwrap();
-        }
-        writeln!(r).unwrap();
+    /**
+    Returns the index of the first grapheme of this string slice that matches the pattern before `n`
+    graphemes
 
-        // Content rows
-        let mut position = 0;
-        for (row, line) in self.lines().iter().enumerate() {
-            // Row column header above
-            let max_column = self.shape[row] + usize::from(row == last_row);
-            let e = n_digits(max_column + 1);
-            for n in 0..e {
-                writeln!(
-                    r,
-                    "{row_space} {}",
-                    (0..=max_column)
-                        .map(|x| { format!("{x:0e$}").chars().nth(n).unwrap().to_string() })
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                )
-                .unwrap();
-            }
+    ```
+    use gstring::*;
 
-            // Content row
-            writeln!(
-                r,
-                "{row:01$} {}",
-                line.iter()
-                    .map(|g| match g.as_str() {
-                        "\n" => "\\n",
-                        "\r\n" => "\\r\\n",
-                        _ => g.as_str(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(" "),
-                row_width,
-            )
-            .unwrap();
+    let g = GString::from("abc abc");
 
-            // Position (offset) below
-            let a = position;
-            let b = position + max_column;
-            let e = n_digits(b + 1);
-            for n in 0..e {
-                writeln!(
-                    r,
-                    "{row_space} {}",
-                    (a..=b)
-                        .map(|x| { format!("{x:0e$}").chars().nth(n).unwrap().to_string() })
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                )
-                .unwrap();
-            }
-            writeln!(r).unwrap();
+    assert_eq!(g.find_prev_from(7, &GString::from("abc")), Some(4));
+    assert_eq!(g.find_prev_from(4, &GString::from("abc")), Some(0));
+    assert!(g.find_prev_from(7, &GString::from("nonexistent")).is_none());
+    assert_eq!(g.find_prev_from(7, "abc"), Some(4));
+    ```
 
-            position += self.shape[row] + 1;
-        }
-        r
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn find_prev_from<P: GPattern>(&self, n: usize, pattern: P) -> Option<usize> {
+        pattern
+            .searcher()
+            .rfind_in(&self.data[..n])
+            .map(|r| r.start)
     }
 
     /**
-    Create a [`GStringRefIter`] for iterating graphemes by reference
+    Returns the index of the first grapheme of this string slice that matches the pattern before `n`
+    graphemes
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-
-    let s = GString::from(S);
-    let mut i = s.iter();
+    let g = GString::from("abc abc");
 
-    assert_eq!(i.next().unwrap(), "a\u{310}");
-    assert_eq!(i.next().unwrap(), "e\u{301}");
-    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
-    assert_eq!(i.next(), None);
+    assert_eq!(g.find_prev_from_str(7, "abc"), Some(4));
+    assert_eq!(g.find_prev_from_str(4, "abc"), Some(0));
+    assert!(g.find_prev_from_str(7, "nonexistent").is_none());
     ```
-
-    See also the [`GString::into_iter`] method.
     */
-    #[allow(clippy::iter_without_into_iter)]
+    #[deprecated(
+        since = "0.14.0",
+        note = "use `GString::find_prev_from` which accepts any `GPattern`"
+    )]
     #[must_use]
-    pub fn iter(&self) -> GStringRefIter<'_> {
-        GStringRefIter {
-            gstring: self,
-            index: 0,
-        }
+    pub fn find_prev_from_str(&self, n: usize, pattern: &str) -> Option<usize> {
+        self.find_prev_from(n, pattern)
     }
 
     /**
-    Consume the [`GString`] and convert into a [`GStringIter`] for iterating graphemes
+    Returns the index of the first grapheme of the last match of the pattern
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-
-    let s = GString::from(S);
-    let mut i = s.into_iter();
+    let g = GString::from("abc e\u{301} abc");
 
-    assert_eq!(i.next().unwrap(), "a\u{310}");
-    assert_eq!(i.next().unwrap(), "e\u{301}");
-    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
-    assert_eq!(i.next(), None);
+    assert_eq!(g.rfind("abc"), Some(6));
+    assert_eq!(g.rfind("e\u{301}"), Some(4));
+    assert_eq!(g.rfind("e"), None);
+    assert_eq!(g.rfind(|g: &Grapheme| g == " "), Some(5));
     ```
 
-    See also the [`GString::iter`] method.
+    See also the [`GPattern`] trait.
     */
-    #[allow(clippy::should_implement_trait)]
     #[must_use]
-    pub fn into_iter(self) -> GStringIter {
-        GStringIter {
-            gstring: self,
-            index: 0,
-        }
+    pub fn rfind<P: GPattern>(&self, pattern: P) -> Option<usize> {
+        pattern.searcher().rfind_in(&self.data).map(|r| r.start)
     }
-}
 
-//--------------------------------------------------------------------------------------------------
-// Implementations
-
-impl std::fmt::Display for GString {
     /**
-    Print a [`GString`] directly in [`print`], [`println`], [`eprint`], [`eprintln`], and [`write`]
-    macros or convert to a [`String`] using the [`format`] macro [`to_string`][ToString::to_string]
-    method
+    Iterate the indices of all non-overlapping matches of the pattern
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    let g = GString::from("aaaa e\u{301}e\u{301}");
+
+    assert_eq!(g.find_all("aa").collect::<Vec<_>>(), &[0, 2]);
+    assert_eq!(g.find_all("e\u{301}").collect::<Vec<_>>(), &[5, 6]);
+    assert_eq!(g.find_all("e").count(), 0);
+    assert_eq!(g.find_all(|g: &Grapheme| g == "a").collect::<Vec<_>>(), &[0, 1, 2, 3]);
+    ```
+
+    See also the [`GString::find_all_overlapping`] method.
+    */
+    #[must_use]
+    pub fn find_all<P: GPattern>(&self, pattern: P) -> MatchIndices<'_, P::Searcher> {
+        MatchIndices {
+            ranges: self.match_ranges(pattern),
+        }
+    }
+
+    /**
+    Iterate the indices of all matches of the pattern, including overlapping matches
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("aaaa");
+
+    assert_eq!(g.find_all_overlapping("aa").collect::<Vec<_>>(), &[0, 1, 2]);
+    ```
+
+    See also the [`GString::find_all`] method.
+    */
+    #[must_use]
+    pub fn find_all_overlapping<P: GPattern>(&self, pattern: P) -> MatchIndices<'_, P::Searcher> {
+        MatchIndices {
+            ranges: self.match_ranges_overlapping(pattern),
+        }
+    }
+
+    /**
+    Iterate the ranges of all non-overlapping matches of the pattern
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("abc e\u{301} abc");
+
+    assert_eq!(g.match_ranges("abc").collect::<Vec<_>>(), &[0..3, 6..9]);
+    assert_eq!(g.match_ranges("e\u{301}").collect::<Vec<_>>(), &[4..5]);
+    ```
+
+    An empty pattern matches at every position:
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("ab");
+
+    assert_eq!(g.match_ranges("").collect::<Vec<_>>(), &[0..0, 1..1, 2..2]);
+    ```
+
+    See also the [`GString::match_ranges_overlapping`] method.
+    */
+    #[must_use]
+    pub fn match_ranges<P: GPattern>(&self, pattern: P) -> MatchRanges<'_, P::Searcher> {
+        MatchRanges {
+            haystack: &self.data,
+            searcher: pattern.searcher(),
+            position: 0,
+            overlapping: false,
+        }
+    }
+
+    /**
+    Iterate the ranges of all matches of the pattern, including overlapping matches
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("aaaa");
+
+    assert_eq!(g.match_ranges_overlapping("aaa").collect::<Vec<_>>(), &[0..3, 1..4]);
+    ```
+
+    See also the [`GString::match_ranges`] method.
+    */
+    #[must_use]
+    pub fn match_ranges_overlapping<P: GPattern>(
+        &self,
+        pattern: P,
+    ) -> MatchRanges<'_, P::Searcher> {
+        MatchRanges {
+            haystack: &self.data,
+            searcher: pattern.searcher(),
+            position: 0,
+            overlapping: true,
+        }
+    }
+
+    /**
+    Return the count of non-overlapping matches of the pattern
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("aaaa e\u{301}");
+
+    assert_eq!(g.count_matches("aa"), 2);
+    assert_eq!(g.count_matches("e\u{301}"), 1);
+    assert_eq!(g.count_matches("e"), 0);
+    ```
+    */
+    #[must_use]
+    pub fn count_matches<P: GPattern>(&self, pattern: P) -> usize {
+        self.match_ranges(pattern).count()
+    }
+
+    /**
+    Returns the index of the first grapheme of the first match of the pattern using
+    [`FindOptions`]
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("My Re\u{301}sume\u{301} (RÉSUMÉ)");
+
+    let options = FindOptions {
+        case_insensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(g.find_with("re\u{301}", options), Some(3));
+    assert_eq!(g.find_with("résumé", options), Some(11));
+
+    let options = FindOptions {
+        diacritic_insensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(g.find_with("Resume", options), Some(3));
+    assert_eq!(g.find_with("RESUME", options), Some(11));
+
+    let options = FindOptions {
+        case_insensitive: true,
+        diacritic_insensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(g.find_with("resume", options), Some(3));
+
+    let options = FindOptions {
+        canonical: true,
+        ..Default::default()
+    };
+    assert_eq!(g.find_with("R\u{e9}", options), Some(3));
+    assert_eq!(g.find_with("RE\u{301}", options), Some(11));
+    assert_eq!(g.find_with("Re", options), None);
+    ```
+
+    The returned index is a grapheme position in the original [`GString`].
+    */
+    #[must_use]
+    pub fn find_with(&self, pattern: &str, options: FindOptions) -> Option<usize> {
+        let (haystack, needle) = search_keys(&self.data, pattern, options);
+        SequenceSearcher::new(Cow::Owned(needle))
+            .find_in(&haystack)
+            .map(|r| r.start)
+    }
+
+    /**
+    Returns the index of the first grapheme of the last match of the pattern using [`FindOptions`]
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("My Re\u{301}sume\u{301} (RÉSUMÉ)");
+
+    let options = FindOptions {
+        case_insensitive: true,
+        diacritic_insensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(g.rfind_with("resume", options), Some(11));
+    ```
+    */
+    #[must_use]
+    pub fn rfind_with(&self, pattern: &str, options: FindOptions) -> Option<usize> {
+        let (haystack, needle) = search_keys(&self.data, pattern, options);
+        SequenceSearcher::new(Cow::Owned(needle))
+            .rfind_in(&haystack)
+            .map(|r| r.start)
+    }
+
+    /**
+    Return the ranges of all non-overlapping matches of the pattern using [`FindOptions`]
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("My Re\u{301}sume\u{301} (RÉSUMÉ)");
+
+    let options = FindOptions {
+        case_insensitive: true,
+        diacritic_insensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(g.match_ranges_with("resume", options), &[3..9, 11..17]);
+    ```
+    */
+    #[must_use]
+    pub fn match_ranges_with(&self, pattern: &str, options: FindOptions) -> Vec<Range<usize>> {
+        let (haystack, needle) = search_keys(&self.data, pattern, options);
+        MatchRanges {
+            haystack: &haystack,
+            searcher: SequenceSearcher::new(Cow::Owned(needle)),
+            position: 0,
+            overlapping: false,
+        }
+        .collect()
+    }
+
+    /**
+    Return a reference to the grapheme at `index`
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let g = GString::from(S);
+
+    assert_eq!(g.get(0).unwrap(), "a\u{310}");
+    assert_eq!(g.get(1).unwrap(), "e\u{301}");
+    assert_eq!(g.get(2).unwrap(), "o\u{308}\u{332}");
+    assert!(g.get(3).is_none());
+    ```
+    */
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Grapheme> {
+        (index < self.len()).then(|| &self.data[index])
+    }
+
+    /**
+    Return the count of graphemes
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
 
     let s = GString::from(S);
+    assert_eq!(s.len(), 3);
+
+    let s = GString::from("");
+    assert_eq!(s.len(), 0);
+    ```
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /**
+    Return [`true`] if the [`GString`] has zero graphemes otherwise return [`false`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from("");
+    assert!(s.is_empty());
+
+    let s = GString::from(S);
+    assert!(!s.is_empty());
+    ```
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /**
+    Return [`true`] if the [`GString`]s are canonically equivalent
+
+    ```
+    use gstring::*;
+
+    let precomposed = GString::from("r\u{e9}sum\u{e9}");
+    let decomposed = GString::from("re\u{301}sume\u{301}");
+
+    assert_ne!(precomposed, decomposed);
+    assert!(precomposed.canonical_eq(&decomposed));
+    assert!(!precomposed.canonical_eq(&GString::from("resume")));
+    ```
+
+    Unlike [`PartialEq`], which compares the raw [`char`]s, this compares the canonical
+    decompositions (NFD).
+    */
+    #[must_use]
+    pub fn canonical_eq(&self, other: &GString) -> bool {
+        let nfd = |s: &GString| s.data.iter().flat_map(|g| g.data.nfd()).collect::<Vec<_>>();
+        nfd(self) == nfd(other)
+    }
+
+    /**
+    Return a new [`GString`] with each grapheme in canonical composition (NFC)
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("re\u{301}sume\u{301}\n");
+
+    assert_eq!(s.nfc(), "r\u{e9}sum\u{e9}\n");
+    assert_eq!(s.nfc().len(), s.len());
+    assert_eq!(s.nfc().shape(), s.shape());
+    assert!(!s.is_nfc());
+    assert!(s.nfc().is_nfc());
+    ```
+
+    The result is re-segmented, so a composed grapheme can also split from its neighbors:
+
+    ```
+    use gstring::*;
+
+    // "\u{2194}\u{338}" composes to "\u{21ae}", which does not join emoji with a ZWJ
+    let s = GString::from("\u{2194}\u{338}\u{200d}\u{1f469}");
+    assert_eq!(s.len(), 1);
+
+    assert_eq!(s.nfc().graphemes(), &["\u{21ae}\u{200d}", "\u{1f469}"]);
+    ```
+    */
+    #[must_use]
+    pub fn nfc(&self) -> GString {
+        self.convert_graphemes(|_, g| g.data.nfc().collect()).0
+    }
+
+    /**
+    Return a new [`GString`] with each grapheme in canonical decomposition (NFD)
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("r\u{e9}sum\u{e9}\n");
+
+    assert_eq!(s.nfd(), "re\u{301}sume\u{301}\n");
+    assert_eq!(s.nfd().len(), s.len());
+    assert_eq!(s.nfd().shape(), s.shape());
+    assert!(s.nfd().is_nfd());
+    ```
+
+    The result is re-segmented, so a decomposed grapheme can merge with its neighbors:
+
+    ```
+    use gstring::*;
+
+    // "\u{21ae}" decomposes to "\u{2194}\u{338}", and "\u{2194}" joins emoji with a ZWJ
+    let s = GString::from("\u{21ae}\u{200d}\u{1f469}");
+    assert_eq!(s.len(), 2);
+
+    assert_eq!(s.nfd().graphemes(), &["\u{2194}\u{338}\u{200d}\u{1f469}"]);
+    ```
+    */
+    #[must_use]
+    pub fn nfd(&self) -> GString {
+        self.convert_graphemes(|_, g| g.data.nfd().collect()).0
+    }
+
+    /**
+    Return a new [`GString`] with each grapheme in compatibility composition (NFKC)
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("\u{fb01}x\u{b2}\ne\u{301}");
+
+    assert_eq!(s.nfkc(), "fix2\n\u{e9}");
+    assert_eq!(s.nfkc().shape(), &[4, 0]);
+    assert!(s.nfkc().is_nfkc());
+    ```
+
+    Note that compatibility mappings can expand a grapheme into several graphemes, so the length and
+    positions may change.
+    */
+    #[must_use]
+    pub fn nfkc(&self) -> GString {
+        self.convert_graphemes(|_, g| g.data.nfkc().collect()).0
+    }
+
+    /**
+    Return a new [`GString`] with each grapheme in compatibility decomposition (NFKD)
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("\u{fb01}x\u{b2}\n\u{e9}");
+
+    assert_eq!(s.nfkd(), "fix2\ne\u{301}");
+    assert!(s.nfkd().is_nfkd());
+
+    // The result is re-segmented, so decomposed graphemes can merge with their neighbors
+    let s = GString::from("\u{21ae}\u{200d}\u{1f469}");
+    assert_eq!(s.len(), 2);
+
+    assert_eq!(s.nfkd().graphemes(), &["\u{2194}\u{338}\u{200d}\u{1f469}"]);
+    ```
+
+    Note that compatibility mappings can expand a grapheme into several graphemes, so the length and
+    positions may change.
+    */
+    #[must_use]
+    pub fn nfkd(&self) -> GString {
+        self.convert_graphemes(|_, g| g.data.nfkd().collect()).0
+    }
+
+    /**
+    Return [`true`] if every grapheme is in canonical composition (NFC)
+
+    ```
+    use gstring::*;
+
+    assert!(GString::from("r\u{e9}sum\u{e9}").is_nfc());
+    assert!(!GString::from("r\u{e9}sume\u{301}").is_nfc());
+    assert!(GString::from("").is_nfc());
+    ```
+    */
+    #[must_use]
+    pub fn is_nfc(&self) -> bool {
+        self.data.iter().all(Grapheme::is_nfc)
+    }
+
+    /**
+    Return [`true`] if every grapheme is in canonical decomposition (NFD)
+
+    ```
+    use gstring::*;
+
+    assert!(GString::from("re\u{301}sume\u{301}").is_nfd());
+    assert!(!GString::from("re\u{301}sum\u{e9}").is_nfd());
+    assert!(GString::from("").is_nfd());
+    ```
+    */
+    #[must_use]
+    pub fn is_nfd(&self) -> bool {
+        self.data.iter().all(Grapheme::is_nfd)
+    }
+
+    /**
+    Return [`true`] if every grapheme is in compatibility composition (NFKC)
+
+    ```
+    use gstring::*;
+
+    assert!(GString::from("fix2 \u{e9}").is_nfkc());
+    assert!(!GString::from("\u{fb01}x\u{b2} \u{e9}").is_nfkc());
+    assert!(GString::from("").is_nfkc());
+    ```
+    */
+    #[must_use]
+    pub fn is_nfkc(&self) -> bool {
+        self.data.iter().all(Grapheme::is_nfkc)
+    }
+
+    /**
+    Return [`true`] if every grapheme is in compatibility decomposition (NFKD)
+
+    ```
+    use gstring::*;
+
+    assert!(GString::from("fix2 e\u{301}").is_nfkd());
+    assert!(!GString::from("fix\u{b2} e\u{301}").is_nfkd());
+    assert!(GString::from("").is_nfkd());
+    ```
+    */
+    #[must_use]
+    pub fn is_nfkd(&self) -> bool {
+        self.data.iter().all(Grapheme::is_nfkd)
+    }
+
+    /**
+    Return a new [`GString`] converted to uppercase
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("stra\u{df}e e\u{301}");
+
+    assert_eq!(s.to_uppercase(), "STRASSE E\u{301}");
+    assert_eq!(s.len(), 8);
+    assert_eq!(s.to_uppercase().len(), 9);
+
+    // The result is re-segmented, so converted graphemes can merge with their neighbors
+    let s = GString::from("\u{24dc}\u{200d}\u{1f469}");
+    assert_eq!(s.len(), 2);
+
+    assert_eq!(s.to_uppercase().graphemes(), &["\u{24c2}\u{200d}\u{1f469}"]);
+    ```
+
+    See also the [`GString::convert_case`] method to map positions to the result.
+    */
+    #[must_use]
+    pub fn to_uppercase(&self) -> GString {
+        self.convert_case(Casing::Upper).0
+    }
+
+    /**
+    Return a new [`GString`] converted to lowercase
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("E\u{301}TÉ").to_lowercase(), "e\u{301}té");
+    assert_eq!(GString::from("ΟΔΟΣ ΟΔΟΣ").to_lowercase(), "οδος οδος");
+    ```
+
+    Note that a capital sigma at the end of a word becomes a final sigma.
+    */
+    #[must_use]
+    pub fn to_lowercase(&self) -> GString {
+        self.convert_case(Casing::Lower).0
+    }
+
+    /**
+    Return a new [`GString`] converted to title case
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("the e\u{301}LAN of o'neil-smith, 3RD ed.");
+
+    assert_eq!(s.to_titlecase(), "The E\u{301}lan Of O'neil-Smith, 3rd Ed.");
+    assert_eq!(GString::from("ǆungla").to_titlecase(), "ǅungla");
+
+    // The result is re-segmented, so converted graphemes can merge with their neighbors
+    let s = GString::from("\u{24dc}\u{200d}\u{1f469}");
+
+    assert_eq!(s.to_titlecase().graphemes(), &["\u{24c2}\u{200d}\u{1f469}"]);
+    ```
+
+    The first grapheme of each word (per Unicode word segmentation) is converted to title case and
+    all other graphemes are converted to lowercase.
+    */
+    #[must_use]
+    pub fn to_titlecase(&self) -> GString {
+        self.convert_case(Casing::Title).0
+    }
+
+    /**
+    Return a new [`GString`] case folded for case-insensitive comparison
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("Stra\u{df}e").case_fold(), "strasse");
+    assert_eq!(GString::from("STRASSE").case_fold(), "strasse");
+    assert_eq!(GString::from("ΟΔΟΣ").case_fold(), GString::from("οδος").case_fold());
+    assert_eq!(GString::from("\u{1e9e}").case_fold(), "ss");
+    ```
+
+    This is the full case folding used by [`FindOptions::case_insensitive`].
+    */
+    #[must_use]
+    pub fn case_fold(&self) -> GString {
+        self.convert_case(Casing::Fold).0
+    }
+
+    /**
+    Return a new [`GString`] converted to a [`Casing`] and a [`PositionMap`] from positions in
+    this [`GString`] to positions in the new [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("\u{df}e\u{301}x");
+    let (upper, map) = s.convert_case(Casing::Upper);
+
+    assert_eq!(upper, "SSE\u{301}X");
+    assert_eq!(map.get(0), Some(0..2));
+    assert_eq!(map.get(1), Some(2..3));
+    assert_eq!(map.get(2), Some(3..4));
+    assert_eq!(map.get(3), None);
+    assert_eq!(map.position(2), Some(3));
+    assert_eq!(map.position(3), Some(4));
+    ```
+
+    Each grapheme is converted independently, so a grapheme may become zero or more graphemes.
+    The result is re-segmented, so a converted grapheme can merge with its neighbors; the merged
+    grapheme is mapped from the first of them and the others map to an empty range after it:
+
+    ```
+    use gstring::*;
+
+    // "\u{24dc}" uppercases to "\u{24c2}", which joins emoji with a ZWJ
+    let s = GString::from("\u{24dc}\u{200d}\u{1f469}");
+    let (upper, map) = s.convert_case(Casing::Upper);
+
+    assert_eq!(s.len(), 2);
+    assert_eq!(upper.graphemes(), &["\u{24c2}\u{200d}\u{1f469}"]);
+    assert_eq!(map.ranges(), &[0..1, 1..1]);
+    ```
+    */
+    #[must_use]
+    pub fn convert_case(&self, casing: Casing) -> (GString, PositionMap) {
+        let word_starts = if casing == Casing::Title {
+            word_starts(&self.data)
+        } else {
+            vec![]
+        };
+        self.convert_graphemes(|i, g| match casing {
+            Casing::Upper => g.data.to_uppercase(),
+            Casing::Title if word_starts[i] => titlecase(&g.data),
+            Casing::Lower | Casing::Title => {
+                if is_final_sigma(&self.data, i) {
+                    g.data.replacen('Σ', "ς", 1).to_lowercase()
+                } else {
+                    g.data.to_lowercase()
+                }
+            }
+            Casing::Fold => fold_case(&g.data),
+        })
+    }
+
+    /**
+    Convert to a `snake_case` identifier
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("Café Menu").to_snake_case(), "café_menu");
+    assert_eq!(GString::from("XMLHttpRequest").to_snake_case(), "xml_http_request");
+    assert_eq!(GString::from("version2Update").to_snake_case(), "version2_update");
+    assert_eq!(GString::from("HTTP2Server").to_snake_case(), "http2_server");
+    assert_eq!(GString::from("party 🎉 time!").to_snake_case(), "party_🎉_time");
+    assert_eq!(
+        GString::from("Jose\u{301}'s re\u{301}sume\u{301}").to_snake_case(),
+        "jose\u{301}_s_re\u{301}sume\u{301}",
+    );
+    ```
+
+    Words are found with Unicode word segmentation and further split at punctuation and at
+    lowercase or digit to uppercase transitions; graphemes are never split.
+    */
+    #[must_use]
+    pub fn to_snake_case(&self) -> GString {
+        self.join_words("_", |_, word| word.to_lowercase())
+    }
+
+    /**
+    Convert to a `kebab-case` identifier
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("Café Menu").to_kebab_case(), "café-menu");
+    assert_eq!(GString::from("fooBar_baz").to_kebab_case(), "foo-bar-baz");
+    ```
+
+    See also the [`GString::to_snake_case`] method.
+    */
+    #[must_use]
+    pub fn to_kebab_case(&self) -> GString {
+        self.join_words("-", |_, word| word.to_lowercase())
+    }
+
+    /**
+    Convert to a `camelCase` identifier
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("Café Menu").to_camel_case(), "caféMenu");
+    assert_eq!(GString::from("e\u{301}cole normale").to_camel_case(), "e\u{301}coleNormale");
+    assert_eq!(GString::from("party 🎉 time!").to_camel_case(), "party🎉Time");
+    ```
+
+    See also the [`GString::to_snake_case`] method.
+    */
+    #[must_use]
+    pub fn to_camel_case(&self) -> GString {
+        self.join_words("", |i, word| {
+            if i == 0 {
+                word.to_lowercase()
+            } else {
+                word.to_titlecase()
+            }
+        })
+    }
+
+    /**
+    Convert to a `PascalCase` identifier
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("Café Menu").to_pascal_case(), "CaféMenu");
+    assert_eq!(GString::from("e\u{301}cole normale").to_pascal_case(), "E\u{301}coleNormale");
+    ```
+
+    See also the [`GString::to_snake_case`] method.
+    */
+    #[must_use]
+    pub fn to_pascal_case(&self) -> GString {
+        self.join_words("", |_, word| word.to_titlecase())
+    }
+
+    /**
+    Convert to a `SCREAMING_SNAKE_CASE` identifier
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("Café Menu").to_screaming_snake_case(), "CAFÉ_MENU");
+    assert_eq!(GString::from("straße").to_screaming_snake_case(), "STRASSE");
+    ```
+
+    See also the [`GString::to_snake_case`] method.
+    */
+    #[must_use]
+    pub fn to_screaming_snake_case(&self) -> GString {
+        self.join_words("_", |_, word| word.to_uppercase())
+    }
+
+    /// Convert each identifier word and join them with a separator
+    fn join_words(&self, separator: &str, f: impl Fn(usize, GString) -> GString) -> GString {
+        let separator = graphemes(separator);
+        let mut data = vec![];
+        for (i, word) in identifier_words(&self.data).into_iter().enumerate() {
+            if i > 0 {
+                data.extend_from_slice(&separator);
+            }
+            data.append(&mut f(i, self.with_data(word)).data);
+        }
+        self.with_data(data)
+    }
+
+    /// Create a new [`GString`] from graphemes and a newline policy
+    fn from_data(data: Vec<Grapheme>, newline_policy: NewlinePolicy) -> GString {
+        let shape = calc_shape(&data, newline_policy);
+        GString {
+            data,
+            shape,
+            newline_policy,
+        }
+    }
+
+    /// Create a new [`GString`] from graphemes with the same newline policy
+    fn with_data(&self, data: Vec<Grapheme>) -> GString {
+        GString::from_data(data, self.newline_policy)
+    }
+
+    /// Create a [`GStr`] view of graphemes with the same newline policy
+    fn view<'a>(&self, data: &'a [Grapheme]) -> GStr<'a> {
+        GStr {
+            data,
+            newline_policy: self.newline_policy,
+        }
+    }
+
+    /// Append a [`&str`], re-segmenting it together with the last grapheme so that clusters
+    /// spanning the join point are merged
+    fn append(&mut self, string: &str) {
+        let mut joined = self.data.pop().map(String::from).unwrap_or_default();
+        joined.push_str(string);
+        self.data.append(&mut graphemes(&joined));
+        self.shape = calc_shape(&self.data, self.newline_policy);
+    }
+
+    /**
+    Create a new [`GString`] by converting each grapheme (with its index) to a [`String`] and
+    re-segmenting the result, and a [`PositionMap`] from the graphemes to the new graphemes
+
+    Converted graphemes can merge with their neighbors, so each grapheme maps to the new graphemes
+    between the first grapheme boundaries at or after the start and end of its conversion.
+    */
+    fn convert_graphemes(&self, f: impl Fn(usize, &Grapheme) -> String) -> (GString, PositionMap) {
+        let mut text = String::new();
+        let mut bytes = vec![];
+        for (i, g) in self.data.iter().enumerate() {
+            let start = text.len();
+            text.push_str(&f(i, g));
+            bytes.push(start..text.len());
+        }
+        let data = graphemes(&text);
+        let offsets = byte_offsets(&data);
+        let index = |byte: usize| offsets.partition_point(|&o| o < byte);
+        let map = PositionMap {
+            ranges: bytes
+                .into_iter()
+                .map(|b| index(b.start)..index(b.end))
+                .collect(),
+            len: data.len(),
+        };
+        (self.with_data(data), map)
+    }
+
+    /**
+    Return a [`Vec`] of [`char`]s
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const C: &[char] = &['a', '\u{310}', 'e', '\u{301}', 'o', '\u{308}', '\u{332}'];
+
+    let c = GString::from(S).chars();
+
+    assert_eq!(c, C);
+    assert_eq!(c.len(), C.len());
+    ```
+    */
+    #[must_use]
+    pub fn chars(&self) -> Vec<char> {
+        self.data.iter().flat_map(Grapheme::chars).collect()
+    }
+
+    /**
+    Return a [`Vec`] of [`u8`]s
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const B: &[u8] = &[0x61, 0xcc, 0x90, 0x65, 0xcc, 0x81, 0x6f, 0xcc, 0x88, 0xcc, 0xb2];
+
+    let b = GString::from(S).bytes();
+
+    assert_eq!(b, B);
+    assert_eq!(b.len(), B.len());
+    ```
+    */
+    #[must_use]
+    pub fn bytes(&self) -> Vec<u8> {
+        self.data.iter().flat_map(Grapheme::bytes).collect()
+    }
+
+    /**
+    Return a [`GStr`] view of all graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}");
+
+    assert_eq!(s.as_gstr(), "a\u{310}e\u{301}");
+    ```
+    */
+    #[must_use]
+    pub fn as_gstr(&self) -> GStr<'_> {
+        self.view(&self.data)
+    }
+
+    /**
+    Iterate the pieces between matches of a pattern
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a,e\u{301},,o\u{308}\u{332}");
+
+    assert_eq!(s.split(",").collect::<Vec<_>>(), &["a", "e\u{301}", "", "o\u{308}\u{332}"]);
+    assert_eq!(s.split("e").count(), 1);
+    assert_eq!(s.split(|g: &Grapheme| g.chars().len() > 1).collect::<Vec<_>>(), &["a,", ",,", ""]);
+    assert_eq!(GString::from("").split(",").collect::<Vec<_>>(), &[""]);
+    assert_eq!(GString::from("ab").split("").collect::<Vec<_>>(), &["", "a", "b", ""]);
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn split<P: GPattern>(&self, pattern: P) -> Split<'_, P::Searcher> {
+        self.splitn(usize::MAX, pattern)
+    }
+
+    /**
+    Iterate the pieces between matches of a pattern in reverse order
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a,e\u{301},,o\u{308}\u{332}");
+
+    assert_eq!(s.rsplit(",").collect::<Vec<_>>(), &["o\u{308}\u{332}", "", "e\u{301}", "a"]);
+    assert_eq!(GString::from("ab").rsplit("").collect::<Vec<_>>(), &["", "b", "a", ""]);
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn rsplit<P: GPattern>(&self, pattern: P) -> RSplit<'_, P::Searcher> {
+        RSplit {
+            haystack: &self.data,
+            searcher: pattern.searcher(),
+            end: self.len(),
+            search_end: Some(self.len()),
+            finished: false,
+            newline_policy: self.newline_policy,
+        }
+    }
+
+    /**
+    Iterate at most `n` pieces between matches of a pattern
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a,e\u{301},o\u{308}\u{332}");
+
+    assert_eq!(s.splitn(2, ",").collect::<Vec<_>>(), &["a", "e\u{301},o\u{308}\u{332}"]);
+    assert_eq!(s.splitn(1, ",").collect::<Vec<_>>(), &["a,e\u{301},o\u{308}\u{332}"]);
+    assert_eq!(s.splitn(0, ",").count(), 0);
+    ```
+
+    The last piece contains the remainder of the [`GString`].
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn splitn<P: GPattern>(&self, n: usize, pattern: P) -> Split<'_, P::Searcher> {
+        Split {
+            matches: self.match_ranges(pattern),
+            start: 0,
+            remaining: n,
+            terminator: false,
+            finished: n == 0,
+            newline_policy: self.newline_policy,
+        }
+    }
+
+    /**
+    Iterate the pieces terminated by matches of a pattern
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a;e\u{301};");
+
+    assert_eq!(s.split_terminator(";").collect::<Vec<_>>(), &["a", "e\u{301}"]);
+    assert_eq!(s.split(";").collect::<Vec<_>>(), &["a", "e\u{301}", ""]);
+    ```
+
+    Equivalent to [`GString::split`] except that a trailing empty piece is skipped.
+    */
+    #[must_use]
+    pub fn split_terminator<P: GPattern>(&self, pattern: P) -> Split<'_, P::Searcher> {
+        Split {
+            terminator: true,
+            ..self.split(pattern)
+        }
+    }
+
+    /**
+    Iterate the non-empty pieces between whitespace graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("  a\u{310}\te\u{301}\r\n o\u{308}\u{332}\u{3000}");
+
+    assert_eq!(
+        s.split_whitespace().collect::<Vec<_>>(),
+        &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"],
+    );
+    ```
+
+    Whitespace graphemes are classified by [`Grapheme::is_whitespace`].
+    */
+    #[must_use]
+    pub fn split_whitespace(&self) -> SplitWhitespace<'_> {
+        SplitWhitespace {
+            data: &self.data,
+            newline_policy: self.newline_policy,
+        }
+    }
+
+    /**
+    Divide into two new [`GString`]s at an index
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}\ne\u{301}o\u{308}\u{332}");
+
+    let (a, b) = s.split_at(2);
+
+    assert_eq!(a, "a\u{310}\n");
+    assert_eq!(a.shape(), &[1, 0]);
+    assert_eq!(b, "e\u{301}o\u{308}\u{332}");
+    assert_eq!(b.shape(), &[1]);
+    ```
+
+    # Panics
+
+    Panics if `index` is greater than the length
+    */
+    #[must_use]
+    pub fn split_at(&self, index: usize) -> (GString, GString) {
+        (self.slice(0..index), self.slice(index..self.len()))
+    }
+
+    /**
+    Split into two at an index, returning the graphemes after the index as a new [`GString`]
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\u{310}\ne\u{301}o\u{308}\u{332}");
+
+    let b = s.split_off(2);
+
+    assert_eq!(s, "a\u{310}\n");
+    assert_eq!(s.shape(), &[1, 0]);
+    assert_eq!(b, "e\u{301}o\u{308}\u{332}");
+    assert_eq!(b.shape(), &[1]);
+    ```
+
+    # Panics
+
+    Panics if `index` is greater than the length
+    */
+    #[must_use]
+    pub fn split_off(&mut self, index: usize) -> GString {
+        let data = self.data.split_off(index);
+        self.shape = calc_shape(&self.data, self.newline_policy);
+        self.with_data(data)
+    }
+
+    /**
+    Return a [`GStr`] view without leading and trailing whitespace graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from(" \u{3000}a\u{310} e\u{301}\r\n");
+
+    assert_eq!(s.trim(), "a\u{310} e\u{301}");
+    assert_eq!(s.trim_start(), "a\u{310} e\u{301}\r\n");
+    assert_eq!(s.trim_end(), " \u{3000}a\u{310} e\u{301}");
+    assert!(GString::from(" \t ").trim().is_empty());
+
+    // A space followed by a combining mark is a single grapheme that is not whitespace
+    assert_eq!(GString::from(" \u{301} ").trim(), " \u{301}");
+    ```
+
+    Whitespace graphemes are classified by [`Grapheme::is_whitespace`].
+    */
+    #[must_use]
+    pub fn trim(&self) -> GStr<'_> {
+        self.trim_matches(Grapheme::is_whitespace)
+    }
+
+    /**
+    Return a [`GStr`] view without leading whitespace graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("\u{3000}\t a\u{310} e\u{301}\n");
+
+    assert_eq!(s.trim_start(), "a\u{310} e\u{301}\n");
+    assert_eq!(s.trim_start().len(), 4);
+    assert!(GString::from("\r\n ").trim_start().is_empty());
+    ```
+
+    See [`GString::trim`].
+    */
+    #[must_use]
+    pub fn trim_start(&self) -> GStr<'_> {
+        let start = self
+            .data
+            .iter()
+            .position(|g| !g.is_whitespace())
+            .unwrap_or(self.len());
+        self.view(&self.data[start..])
+    }
+
+    /**
+    Return a [`GStr`] view without trailing whitespace graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from(" a\u{310} e\u{301}\u{3000}\r\n");
+
+    assert_eq!(s.trim_end(), " a\u{310} e\u{301}");
+    assert_eq!(s.trim_end().len(), 4);
+    assert!(GString::from(" \r\n").trim_end().is_empty());
+    ```
+
+    See [`GString::trim`].
+    */
+    #[must_use]
+    pub fn trim_end(&self) -> GStr<'_> {
+        let end = self
+            .data
+            .iter()
+            .rposition(|g| !g.is_whitespace())
+            .map_or(0, |i| i + 1);
+        self.view(&self.data[..end])
+    }
+
+    /**
+    Return a [`GStr`] view with all leading and trailing matches of a pattern removed
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301}e\u{301}ae\u{301}e\u{301}");
+
+    assert_eq!(s.trim_matches("e\u{301}"), "a");
+    assert_eq!(s.trim_matches("e"), s);
+    assert_eq!(s.trim_matches(|g: &Grapheme| g != "a"), "a");
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn trim_matches<P: GPattern>(&self, pattern: P) -> GStr<'_> {
+        let searcher = pattern.searcher();
+        let mut data = &self.data[..];
+        while let Some(n) = searcher.prefix_in(data).filter(|n| *n > 0) {
+            data = &data[n..];
+        }
+        while let Some(n) = searcher.suffix_in(data).filter(|n| *n > 0) {
+            data = &data[..data.len() - n];
+        }
+        self.view(data)
+    }
+
+    /**
+    Return a [`GStr`] view after a prefix matching a pattern or [`None`] if it does not start with
+    a match
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(s.strip_prefix("e\u{301}").unwrap(), "o\u{308}\u{332}");
+    assert_eq!(s.strip_prefix("e"), None);
+    assert_eq!(s.strip_prefix("").unwrap(), s);
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn strip_prefix<P: GPattern>(&self, pattern: P) -> Option<GStr<'_>> {
+        let n = pattern.searcher().prefix_in(&self.data)?;
+        Some(self.view(&self.data[n..]))
+    }
+
+    /**
+    Return a [`GStr`] view before a suffix matching a pattern or [`None`] if it does not end with
+    a match
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(s.strip_suffix("o\u{308}\u{332}").unwrap(), "e\u{301}");
+    assert_eq!(s.strip_suffix("\u{332}"), None);
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn strip_suffix<P: GPattern>(&self, pattern: P) -> Option<GStr<'_>> {
+        let n = pattern.searcher().suffix_in(&self.data)?;
+        Some(self.view(&self.data[..self.len() - n]))
+    }
+
+    /**
+    Return [`true`] if it starts with a match of the pattern
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301}o\u{308}\u{332}");
+
+    assert!(s.starts_with("e\u{301}"));
+    assert!(!s.starts_with("e"));
+    let set = [Grapheme::from("a").unwrap(), Grapheme::from("e\u{301}").unwrap()];
+    assert!(s.starts_with(&set[..]));
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn starts_with<P: GPattern>(&self, pattern: P) -> bool {
+        pattern.searcher().prefix_in(&self.data).is_some()
+    }
+
+    /**
+    Return [`true`] if it ends with a match of the pattern
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301}o\u{308}\u{332}");
+
+    assert!(s.ends_with("o\u{308}\u{332}"));
+    assert!(!s.ends_with("\u{332}"));
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn ends_with<P: GPattern>(&self, pattern: P) -> bool {
+        pattern.searcher().suffix_in(&self.data).is_some()
+    }
+
+    /**
+    Return [`true`] if it contains a match of the pattern
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301}o\u{308}\u{332}");
+
+    assert!(s.contains("o\u{308}\u{332}"));
+    assert!(!s.contains("e"));
+    assert!(!s.contains("o\u{308}"));
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn contains<P: GPattern>(&self, pattern: P) -> bool {
+        pattern.searcher().find_in(&self.data).is_some()
+    }
+
+    /**
+    Return the [`NewlinePolicy`]
+
+    See [`GString::set_newline_policy`].
+    */
+    #[must_use]
+    pub fn newline_policy(&self) -> NewlinePolicy {
+        self.newline_policy
+    }
+
+    /**
+    Set the [`NewlinePolicy`] and recalculate the shape
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("ab\rc\u{2028}d\ne");
+
+    assert_eq!(s.newline_policy(), NewlinePolicy::LfCrlf);
+    assert_eq!(s.newlines(), &[6]);
+    assert_eq!(s.shape(), &[6, 0]);
+
+    s.set_newline_policy(NewlinePolicy::LfCrlfCr);
+
+    assert_eq!(s.newlines(), &[2, 6]);
+    assert_eq!(s.lines(), &["ab\r", "c\u{2028}d\n", "e"]);
+    assert_eq!(s.shape(), &[2, 3, 0]);
+
+    s.set_newline_policy(NewlinePolicy::Unicode);
+
+    assert_eq!(s.newlines(), &[2, 4, 6]);
+    assert_eq!(s.shape(), &[2, 1, 1, 0]);
+    assert_eq!(s.coordinates(5), Some((2, 0)));
+    assert_eq!(s.position((3, 0)), Some(7));
+
+    // Derived GStrings keep the policy
+    assert_eq!(s.slice(0..3).newline_policy(), NewlinePolicy::Unicode);
+    assert_eq!(s.lines()[0].newline_policy(), NewlinePolicy::Unicode);
+    ```
+
+    The default policy is [`NewlinePolicy::LfCrlf`].
+    A [`GString`] created from another (for example by [`GString::slice`] or [`GString::lines`])
+    has the same policy.
+    */
+    pub fn set_newline_policy(&mut self, newline_policy: NewlinePolicy) {
+        self.newline_policy = newline_policy;
+        self.shape = calc_shape(&self.data, newline_policy);
+    }
+
+    /**
+    Return the [`GString`] with a [`NewlinePolicy`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\rb").with_newline_policy(NewlinePolicy::LfCrlfCr);
+
+    assert_eq!(s.shape(), &[1, 0]);
+    ```
+
+    See [`GString::set_newline_policy`].
+    */
+    #[must_use]
+    pub fn with_newline_policy(mut self, newline_policy: NewlinePolicy) -> GString {
+        self.set_newline_policy(newline_policy);
+        self
+    }
+
+    /**
+    Count each kind of line ending
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\r\nb\nc\r\nd\re");
+
+    let stats = s.line_ending_stats();
+
+    assert_eq!(stats.get(LineEnding::Crlf), 2);
+    assert_eq!(stats.get(LineEnding::Lf), 1);
+    assert_eq!(stats.get(LineEnding::Cr), 1);
+    assert_eq!(stats.total(), 4);
+    ```
+
+    Every [`LineEnding`] grapheme is counted regardless of the [`NewlinePolicy`], so mixed line
+    endings are detected even when some of them do not end lines under the policy.
+    */
+    #[must_use]
+    pub fn line_ending_stats(&self) -> LineEndingStats {
+        let mut r = LineEndingStats::default();
+        for e in self.data.iter().filter_map(LineEnding::from_grapheme) {
+            r.counts[e as usize] += 1;
+        }
+        r
+    }
+
+    /**
+    Return the most frequent line ending or [`None`] if there are no line endings
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("a\r\nb\nc\r\n").dominant_line_ending(), Some(LineEnding::Crlf));
+    assert_eq!(GString::from("a\r\nb\n").dominant_line_ending(), Some(LineEnding::Lf));
+    assert_eq!(GString::from("a").dominant_line_ending(), None);
+    ```
+
+    Ties are resolved by the order of [`LineEnding::ALL`].
+    See [`GString::line_ending_stats`] and [`LineEndingStats::dominant`].
+    */
+    #[must_use]
+    pub fn dominant_line_ending(&self) -> Option<LineEnding> {
+        self.line_ending_stats().dominant()
+    }
+
+    /**
+    Rewrite all newline graphemes to a line ending and return the position remap
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\r\nb\nc\r\n");
+
+    let map = s.normalize_line_endings(LineEnding::Lf);
+
+    assert_eq!(s, "a\nb\nc\n");
+    assert_eq!(s.shape(), &[1, 1, 1, 0]);
+    assert_eq!(map.position(4), Some(4));
+    assert_eq!(map.position(6), Some(6));
+
+    let mut s = GString::from("a\rb\u{2028}c\n").with_newline_policy(NewlinePolicy::Unicode);
+
+    s.normalize_line_endings(LineEnding::Crlf);
+
+    assert_eq!(s, "a\r\nb\r\nc\r\n");
+    assert_eq!(s.line_ending_stats().get(LineEnding::Crlf), 3);
+
+    // A lone "\r" is not a newline by default, so it merges with the rewritten "\n"
+    let mut s = GString::from("\r\r\n");
+
+    let map = s.normalize_line_endings(LineEnding::Lf);
+
+    assert_eq!(s.graphemes(), &["\r\n"]);
+    assert_eq!(map.ranges(), &[0..1, 1..1]);
+    ```
+
+    Only newline graphemes according to the [`NewlinePolicy`] are rewritten, so set a policy like
+    [`NewlinePolicy::LfCrlfCr`] first to also rewrite lone `"\r"`s, and the line ending should be a
+    newline according to the policy to keep the same lines.
+    The result is re-segmented, so a rewritten line ending can merge with a neighboring grapheme;
+    the map then gives an empty range for original graphemes merged into the previous grapheme.
+    */
+    pub fn normalize_line_endings(&mut self, line_ending: LineEnding) -> PositionMap {
+        let mut text = String::new();
+        let mut bytes = vec![];
+        for g in &self.data {
+            let start = text.len();
+            if g.is_newline_with(self.newline_policy) {
+                text.push_str(line_ending.as_str());
+            } else {
+                text.push_str(&g.data);
+            }
+            bytes.push(start..text.len());
+        }
+        self.data = graphemes(&text);
+        self.shape = calc_shape(&self.data, self.newline_policy);
+        let offsets = byte_offsets(&self.data);
+        let index = |byte: usize| offsets.partition_point(|&o| o < byte);
+        PositionMap {
+            ranges: bytes
+                .into_iter()
+                .map(|b| index(b.start)..index(b.end))
+                .collect(),
+            len: self.data.len(),
+        }
+    }
+
+    /**
+    Split into lines as a [`Vec`] of [`GString`]s
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("abc\ndef").lines(), &["abc\n", "def"]);
+    assert_eq!(GString::from("abc\n").lines(), &["abc\n", ""]);
+    assert_eq!(GString::from("\ndef").lines(), &["\n", "def"]);
+    ```
+
+    Note that unlike [`str::lines`], this method includes the original newline graphemes at the end
+    of each line.
+    Newline graphemes are determined by the [`NewlinePolicy`].
+    */
+    #[must_use]
+    pub fn lines(&self) -> Vec<GString> {
+        lines(&self.data, self.newline_policy)
+    }
+
+    /**
+    Iterate the lines as [`GStr`] views without their newline graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("abc\r\ne\u{301}\n\ndef\n");
+
+    assert_eq!(s.lines_without_endings().collect::<Vec<_>>(), &["abc", "e\u{301}", "", "def"]);
+    assert_eq!(GString::from("abc").lines_without_endings().collect::<Vec<_>>(), &["abc"]);
+    assert_eq!(GString::from("").lines_without_endings().count(), 0);
+    ```
+
+    Like [`str::lines`], the empty line after a final newline is not included.
+    Newline graphemes are determined by the [`NewlinePolicy`].
+    */
+    #[must_use]
+    pub fn lines_without_endings(&self) -> LinesWithoutEndings<'_> {
+        LinesWithoutEndings {
+            data: &self.data,
+            newline_policy: self.newline_policy,
+        }
+    }
+
+    /**
+    Return the count of lines
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("abc\ndef").line_count(), 2);
+    assert_eq!(GString::from("abc\n").line_count(), 2);
+    assert_eq!(GString::from("").line_count(), 1);
+    ```
+
+    Equal to the length of [`GString::lines`] and [`GString::shape`].
+    */
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.shape.len()
+    }
+
+    /**
+    Return a [`GStr`] view of a line including its newline grapheme or [`None`] if the line does
+    not exist
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("abc\r\ne\u{301}\ndef");
+
+    assert_eq!(s.line(0).unwrap(), "abc\r\n");
+    assert_eq!(s.line(1).unwrap(), "e\u{301}\n");
+    assert_eq!(s.line(2).unwrap(), "def");
+    assert_eq!(s.line(3), None);
+    ```
+    */
+    #[must_use]
+    pub fn line(&self, n: usize) -> Option<GStr<'_>> {
+        self.line_range(n).map(|r| self.view(&self.data[r]))
+    }
+
+    /**
+    Return the position range of a line including its newline grapheme or [`None`] if the line
+    does not exist
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("abc\r\ne\u{301}\ndef\n");
+
+    assert_eq!(s.line_range(0), Some(0..4));
+    assert_eq!(s.line_range(1), Some(4..6));
+    assert_eq!(s.line_range(2), Some(6..10));
+    assert_eq!(s.line_range(3), Some(10..10));
+    assert_eq!(s.line_range(4), None);
+    ```
+
+    See also the [`GString::line_range_without_ending`] method.
+    */
+    #[must_use]
+    pub fn line_range(&self, n: usize) -> Option<Range<usize>> {
+        // Every line except the last ends with a newline, so its length is its max column + 1
+        let last_row = self.shape.len() - 1;
+        (n <= last_row).then(|| {
+            let start = self.shape[..n].iter().map(|c| c + 1).sum::<usize>();
+            let end = if n == last_row {
+                self.len()
+            } else {
+                start + self.shape[n] + 1
+            };
+            start..end
+        })
+    }
+
+    /**
+    Return the position range of a line excluding its newline grapheme or [`None`] if the line
+    does not exist
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("abc\r\ne\u{301}\ndef\n");
+
+    assert_eq!(s.line_range_without_ending(0), Some(0..3));
+    assert_eq!(s.line_range_without_ending(1), Some(4..5));
+    assert_eq!(s.line_range_without_ending(2), Some(6..9));
+    assert_eq!(s.line_range_without_ending(3), Some(10..10));
+    assert_eq!(s.line_range_without_ending(4), None);
+    ```
+
+    See also the [`GString::line_range`] method.
+    */
+    #[must_use]
+    pub fn line_range_without_ending(&self, n: usize) -> Option<Range<usize>> {
+        self.line_range(n).map(|r| {
+            let end = if n < self.shape.len() - 1 {
+                r.end - 1
+            } else {
+                r.end
+            };
+            r.start..end
+        })
+    }
+
+    /**
+    Insert a line of text before a row
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc\r\ndef");
+
+    s.insert_line(1, "e\u{301}").unwrap();
+    assert_eq!(s, "abc\r\ne\u{301}\r\ndef");
+
+    s.insert_line(3, "ghi").unwrap();
+    assert_eq!(s, "abc\r\ne\u{301}\r\ndef\r\nghi");
+    assert_eq!(s.shape(), &[3, 1, 3, 2]);
+
+    assert!(s.insert_line(5, "x").is_err());
+
+    // Lone "\r"s are not newlines by default, so they are not used for new lines
+    let mut s = GString::from("a\rb\rc\nd");
+
+    s.insert_line(0, "x").unwrap();
+    assert_eq!(s, "x\na\rb\rc\nd");
+    ```
+
+    A `row` equal to the line count appends a line at the end.
+    New newline graphemes use the most frequent line ending that is a newline according to the
+    [`NewlinePolicy`], or `"\n"` if there is none.
+
+    # Errors
+
+    Returns an error if `row` is greater than the line count
+    */
+    pub fn insert_line(&mut self, row: usize, text: &str) -> Result<()> {
+        self.check_rows(&(row..row))?;
+        let mut parts = self.line_parts();
+        parts.insert(row, (graphemes(text), None));
+        self.set_line_parts(parts);
+        Ok(())
+    }
+
+    /**
+    Delete a range of rows and return the deleted lines as a new [`GString`]
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc\ndef\nghi\n");
+
+    assert_eq!(s.delete_lines(1..2).unwrap(), "def\n");
+    assert_eq!(s, "abc\nghi\n");
+
+    let mut s = GString::from("abc\ndef\nghi");
+
+    assert_eq!(s.delete_lines(1..).unwrap(), "def\nghi");
+    assert_eq!(s, "abc");
+    assert_eq!(s.shape(), &[2]);
+
+    assert!(s.delete_lines(0..2).is_err());
+    ```
+
+    Deleting the last row also deletes the newline grapheme before it, so the new last row has no
+    newline grapheme.
+
+    # Errors
+
+    Returns an error if `rows` is out of bounds
+    */
+    pub fn delete_lines<R: RangeBounds<usize>>(&mut self, rows: R) -> Result<GString> {
+        let rows = bounds(rows, self.line_count());
+        self.check_rows(&rows)?;
+        let mut parts = self.line_parts();
+        let deleted = parts
+            .drain(rows)
+            .flat_map(|(content, ending)| content.into_iter().chain(ending))
+            .collect();
+        self.set_line_parts(parts);
+        Ok(self.with_data(deleted))
+    }
+
+    /**
+    Move a range of rows up (negative `delta`) or down (positive `delta`)
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc\ndef\nghi");
+
+    s.move_lines(2..3, -2).unwrap();
+    assert_eq!(s, "ghi\nabc\ndef");
+
+    s.move_lines(0..2, 1).unwrap();
+    assert_eq!(s, "def\nghi\nabc");
+
+    assert!(s.move_lines(0..2, 2).is_err());
+
+    // A line ending with a lone "\r" merges with a following "\n" into a "\r\n" newline grapheme
+    let mut s = GString::from("x\nab\r");
+
+    s.move_lines(1..2, -1).unwrap();
+    assert_eq!(s, "ab\r\nx");
+    assert_eq!(s.graphemes(), graphemes("ab\r\nx"));
+    assert_eq!(s.line_count(), 2);
+    assert_eq!(s.line(0).unwrap(), "ab\r\n");
+    ```
+
+    Newline graphemes stay at their rows, so mixed line endings and the trailing newline are kept.
+    Lines are re-segmented where they are joined.
+
+    # Errors
+
+    Returns an error if `rows` is out of bounds or the moved rows would be out of bounds
+    */
+    pub fn move_lines<R: RangeBounds<usize>>(&mut self, rows: R, delta: isize) -> Result<()> {
+        let rows = bounds(rows, self.line_count());
+        self.check_rows(&rows)?;
+        let start = rows
+            .start
+            .checked_add_signed(delta)
+            .filter(|start| start + rows.len() <= self.line_count())
+            .ok_or_else(|| anyhow!("Cannot move rows {rows:?} by {delta}"))?;
+        let parts = self.line_parts();
+        let (mut contents, endings): (Vec<_>, Vec<_>) = parts.into_iter().unzip();
+        let moved = contents.drain(rows).collect::<Vec<_>>();
+        contents.splice(start..start, moved);
+        self.set_line_parts(contents.into_iter().zip(endings).collect());
+        Ok(())
+    }
+
+    /**
+    Duplicate a range of rows, inserting the copies after the original rows
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc\r\ndef");
+
+    s.duplicate_lines(0..1).unwrap();
+    assert_eq!(s, "abc\r\nabc\r\ndef");
+
+    s.duplicate_lines(2..).unwrap();
+    assert_eq!(s, "abc\r\nabc\r\ndef\r\ndef");
+    ```
+
+    # Errors
+
+    Returns an error if `rows` is out of bounds
+    */
+    pub fn duplicate_lines<R: RangeBounds<usize>>(&mut self, rows: R) -> Result<()> {
+        let rows = bounds(rows, self.line_count());
+        self.check_rows(&rows)?;
+        let mut parts = self.line_parts();
+        let copies = parts[rows.clone()].to_vec();
+        parts.splice(rows.end..rows.end, copies);
+        self.set_line_parts(parts);
+        Ok(())
+    }
+
+    /**
+    Swap two rows
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc\r\ndef\nghi");
+
+    s.swap_lines(0, 2).unwrap();
+    assert_eq!(s, "ghi\r\ndef\nabc");
+
+    s.swap_lines(1, 1).unwrap();
+    assert_eq!(s, "ghi\r\ndef\nabc");
+
+    assert!(s.swap_lines(0, 3).is_err());
+
+    // A line ending with a lone "\r" merges with a following "\n" into a "\r\n" newline grapheme
+    let mut s = GString::from("x\nab\r");
+
+    s.swap_lines(0, 1).unwrap();
+    assert_eq!(s.graphemes(), graphemes("ab\r\nx"));
+    assert_eq!(s.line_count(), 2);
+    assert_eq!(s.line(1).unwrap(), "x");
+    ```
+
+    Newline graphemes stay at their rows, like [`GString::move_lines`].
+
+    # Errors
+
+    Returns an error if either row is out of bounds
+    */
+    pub fn swap_lines(&mut self, a: usize, b: usize) -> Result<()> {
+        self.check_rows(&(a.max(b)..a.max(b) + 1))?;
+        if a != b {
+            let mut parts = self.line_parts();
+            parts.swap(a, b);
+            let (ending_a, ending_b) = (parts[a].1.take(), parts[b].1.take());
+            (parts[a].1, parts[b].1) = (ending_b, ending_a);
+            self.set_line_parts(parts);
+        }
+        Ok(())
+    }
+
+    /**
+    Join a range of rows into one row with a separator
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc\ndef\nghi\n");
+
+    s.join_lines(0..2, " ").unwrap();
+    assert_eq!(s, "abc def\nghi\n");
+
+    s.join_lines(.., ", ").unwrap();
+    assert_eq!(s, "abc def, ghi, ");
+    ```
+
+    The joined row keeps the newline grapheme of the last joined row.
+
+    # Errors
+
+    Returns an error if `rows` is out of bounds
+    */
+    pub fn join_lines<R: RangeBounds<usize>>(&mut self, rows: R, separator: &str) -> Result<()> {
+        let rows = bounds(rows, self.line_count());
+        self.check_rows(&rows)?;
+        if rows.len() > 1 {
+            let separator = graphemes(separator);
+            let mut parts = self.line_parts();
+            let mut joined = parts.drain(rows.clone()).collect::<Vec<_>>();
+            let ending = joined.last_mut().and_then(|(_, ending)| ending.take());
+            let content = joined
+                .into_iter()
+                .map(|(content, _)| content)
+                .collect::<Vec<_>>()
+                .join(&separator[..]);
+            parts.insert(rows.start, (content, ending));
+            self.set_line_parts(parts);
+        }
+        Ok(())
+    }
+
+    /**
+    Sort the lines using [`SortOptions`]
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("b\nB\na10\na9\nA1\n");
+
+    s.sort_lines(SortOptions::default());
+    assert_eq!(s, "A1\nB\na10\na9\nb\n");
+
+    s.sort_lines(SortOptions {
+        case_insensitive: true,
+        ..Default::default()
+    });
+    assert_eq!(s, "A1\na10\na9\nB\nb\n");
+
+    s.sort_lines(SortOptions {
+        case_insensitive: true,
+        natural: true,
+    });
+    assert_eq!(s, "A1\na9\na10\nB\nb\n");
+
+    // Equal numbers with different leading zeros are ordered by their chars
+    let mut s = GString::from("a1\na01\na001");
+
+    s.sort_lines(SortOptions {
+        natural: true,
+        ..Default::default()
+    });
+    assert_eq!(s, "a001\na01\na1");
+    ```
+
+    Lines are compared by their [`char`]s (codepoints) like the [`Ord`] implementation of
+    [`GString`].
+    The sort is stable and newline graphemes stay at their rows.
+    The empty last line after a final newline is not sorted, so the trailing newline is kept.
+    */
+    pub fn sort_lines(&mut self, options: SortOptions) {
+        self.permute_lines(|lines| {
+            let key = |line: &Vec<Grapheme>| {
+                let line = line.iter().map(Grapheme::as_str).collect::<String>();
+                if options.case_insensitive {
+                    fold_case(&line)
+                } else {
+                    line
+                }
+            };
+            if options.natural {
+                lines.sort_by_cached_key(|line| NaturalKey(key(line)));
+            } else {
+                lines.sort_by_cached_key(key);
+            }
+        });
+    }
+
+    /**
+    Remove consecutive duplicate lines and return the count of removed lines
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\na\r\nb\na\na\n");
+
+    assert_eq!(s.dedup_lines(), 2);
+    assert_eq!(s, "a\nb\na\n");
+    ```
+
+    Lines are compared without their newline graphemes, and each kept line keeps its newline
+    grapheme.
+    Like [`Vec::dedup`], only consecutive duplicates are removed, so sort the lines first to
+    remove all duplicates.
+    The empty last line after a final newline is kept.
+    */
+    pub fn dedup_lines(&mut self) -> usize {
+        let mut r = 0;
+        self.transform_lines(|parts| {
+            let len = parts.len();
+            parts.dedup_by(|(a, _), (b, _)| a == b);
+            r = len - parts.len();
+        });
+        r
+    }
+
+    /**
+    Reverse the order of the lines
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\nb\r\nc\n");
+
+    s.reverse_lines();
+    assert_eq!(s, "c\nb\r\na\n");
+
+    let mut s = GString::from("a\nb");
+
+    s.reverse_lines();
+    assert_eq!(s, "b\na");
+    ```
+
+    Newline graphemes stay at their rows.
+    The empty last line after a final newline is not moved, so the trailing newline is kept.
+    */
+    pub fn reverse_lines(&mut self) {
+        self.permute_lines(|lines| lines.reverse());
+    }
+
+    /**
+    Shuffle the lines in a deterministic order determined by a seed
+
+    ```
+    use gstring::*;
+
+    let mut s1 = GString::from("a\nb\nc\nd\ne\n");
+    let mut s2 = s1.clone();
+
+    s1.shuffle_lines(42);
+    s2.shuffle_lines(42);
+
+    assert_eq!(s1, s2);
+    assert!(s1.ends_with("\n"));
+
+    let mut lines = s1.lines_without_endings().map(|l| l.to_string()).collect::<Vec<_>>();
+    lines.sort();
+    assert_eq!(lines, &["a", "b", "c", "d", "e"]);
+    ```
+
+    The same seed and lines always give the same order.
+    Newline graphemes stay at their rows.
+    The empty last line after a final newline is not moved, so the trailing newline is kept.
+    */
+    pub fn shuffle_lines(&mut self, seed: u64) {
+        self.permute_lines(|lines| {
+            let mut state = seed;
+            for i in (1..lines.len()).rev() {
+                let j = usize::try_from(splitmix64(&mut state) % (i as u64 + 1)).unwrap_or(i);
+                lines.swap(i, j);
+            }
+        });
+    }
+
+    /// Transform the lines, keeping the empty last line after a final newline
+    fn transform_lines(&mut self, f: impl FnOnce(&mut Vec<(Vec<Grapheme>, Option<Grapheme>)>)) {
+        let mut parts = self.line_parts();
+        let trailing = (parts.len() > 1 && parts.last().is_some_and(|(c, _)| c.is_empty()))
+            .then(|| parts.pop().unwrap());
+        f(&mut parts);
+        parts.extend(trailing);
+        self.set_line_parts(parts);
+    }
+
+    /// Permute the line contents, keeping newline graphemes at their rows and the empty last line
+    /// after a final newline
+    fn permute_lines(&mut self, f: impl FnOnce(&mut Vec<Vec<Grapheme>>)) {
+        self.transform_lines(|parts| {
+            let (mut contents, endings): (Vec<_>, Vec<_>) =
+                std::mem::take(parts).into_iter().unzip();
+            f(&mut contents);
+            *parts = contents.into_iter().zip(endings).collect();
+        });
+    }
+
+    /// Return an error if a range of rows is out of bounds
+    fn check_rows(&self, rows: &Range<usize>) -> Result<()> {
+        if rows.start > rows.end || rows.end > self.line_count() {
+            Err(anyhow!(
+                "Rows {rows:?} are out of bounds for line count {}",
+                self.line_count(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Split into the content and newline grapheme of each line
+    fn line_parts(&self) -> Vec<(Vec<Grapheme>, Option<Grapheme>)> {
+        self.lines()
+            .into_iter()
+            .map(|mut line| {
+                let ending = if line
+                    .data
+                    .last()
+                    .is_some_and(|g| g.is_newline_with(self.newline_policy))
+                {
+                    line.data.pop()
+                } else {
+                    None
+                };
+                (line.data, ending)
+            })
+            .collect()
+    }
+
+    /// Replace the content with lines, giving every line except the last a newline grapheme
+    ///
+    /// The graphemes are re-segmented at each join, so a content ending with a lone `"\r"` merges
+    /// with a following `"\n"` newline grapheme.
+    fn set_line_parts(&mut self, parts: Vec<(Vec<Grapheme>, Option<Grapheme>)>) {
+        let line_ending = self
+            .line_ending_stats()
+            .dominant_by(|e| e.as_str().is_newline_with(self.newline_policy))
+            .unwrap_or(LineEnding::Lf);
+        let last = parts.len().saturating_sub(1);
+        let mut data = vec![];
+        for (i, (content, ending)) in parts.into_iter().enumerate() {
+            let ending = (i < last).then(|| {
+                ending.unwrap_or_else(|| Grapheme {
+                    data: line_ending.as_str().to_string(),
+                })
+            });
+            for piece in [content, ending.into_iter().collect()] {
+                let n = data.len();
+                let joined = n > 0 && !piece.is_empty();
+                data.extend(piece);
+                if joined {
+                    resegment(&mut data, n..n + 1);
+                }
+            }
+        }
+        self.data = data;
+        self.shape = calc_shape(&self.data, self.newline_policy);
+    }
+
+    /**
+    Return the coordinates `(row, column)` for a given position
+
+    ```
+    use gstring::*;
+
+    let g = GString::from("abc\ndef");
+
+    assert_eq!(g.coordinates(0), Some((0, 0)));
+    assert_eq!(g.coordinates(1), Some((0, 1)));
+    assert_eq!(g.coordinates(2), Some((0, 2)));
+    assert_eq!(g.coordinates(3), Some((0, 3)));
+    assert_eq!(g.coordinates(4), Some((1, 0)));
+    assert_eq!(g.coordinates(5), Some((1, 1)));
+    assert_eq!(g.coordinates(6), Some((1, 2)));
+    assert_eq!(g.coordinates(7), Some((1, 3)));
+    assert_eq!(g.coordinates(8), None);
+    ```
+
+    Note that newlines are located at the end of each line.
+    Also a valid coordinate exists at a [`GString`]'s position equal to its length, however no valid
+    coordinate exists for any greater position.
+
+    See also the [`GString::position`] method.
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn coordinates(&self, position: usize) -> Option<(usize, usize)> {
+        (position <= self.len()).then(|| {
+            let n = newline_indices(&self.data[..position], self.newline_policy);
+            let row = n.len();
+            let column = if row == 0 {
+                position
+            } else {
+                position - n.last().unwrap() - 1
+            };
+            (row, column)
+        })
+    }
+
+    /**
+    Return the position for given coordinates `(row, column)`
+
+    ```
+    use gstring::*;
+
+    /*
+      0 1 2 3   column
+    0 a b c \n
+      0 1 2 3   position
+
+      0 1 2 3   column
+    1 d e f
+      4 5 6 7   position
+    */
+
+    let g = GString::from("abc\ndef");
+
+    assert_eq!(g.position((0, 0)), Some(0));
+    assert_eq!(g.position((0, 1)), Some(1));
+    assert_eq!(g.position((0, 2)), Some(2));
+    assert_eq!(g.position((0, 3)), Some(3));
+    assert_eq!(g.position((0, 4)), None);
+    assert_eq!(g.position((1, 0)), Some(4));
+    assert_eq!(g.position((1, 1)), Some(5));
+    assert_eq!(g.position((1, 2)), Some(6));
+    assert_eq!(g.position((1, 3)), Some(7));
+    assert_eq!(g.position((1, 4)), None);
+    assert_eq!(g.position((2, 0)), None);
+
+    let g = GString::from("");
+
+    assert_eq!(g.position((0, 0)), Some(0));
+    assert_eq!(g.position((0, 1)), None);
+    assert_eq!(g.position((1, 0)), None);
+    ```
+
+    Note that newlines are located at the end of each line.
+    Also a valid coordinate exists at a [`GString`]'s position equal to its length, however no valid
+    coordinate exists for any greater position.
+
+    See also the [`GString::coordinates`] method.
+    */
+    #[must_use]
+    pub fn position(&self, coordinates: (usize, usize)) -> Option<usize> {
+        if self.is_empty() {
+            // Empty, so only valid coordinate is `(0, 0)` and position is `0`
+            (coordinates == (0, 0)).then_some(0)
+        } else {
+            // Not empty...
+            match coordinates {
+                (0, 0) => {
+                    // Shortcut `(0, 0)` to `0`
+                    Some(0)
+                }
+                (row, column) => {
+                    // Not `(0, 0)`...
+                    let newlines = self.newlines();
+                    let last_row = newlines.len();
+                    if row <= last_row {
+                        // Valid row
+                        let lines = self.lines();
+                        let last_column = lines[row].len();
+                        if row == last_row && column == last_column {
+                            // Last row and last column
+                            Some(self.len())
+                        } else if column < last_column {
+                            // Valid column
+                            // Sum lengths of prior lines and add the column
+                            Some(lines[..row].iter().map(GString::len).sum::<usize>() + column)
+                        } else {
+                            // Invalid column
+                            None
+                        }
+                    } else {
+                        // Invalid row
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /**
+    Return the indices of all newlines
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("abc\ndef").newlines(), &[3]);
+    assert_eq!(GString::from("abc\ndef\n").newlines(), &[3, 7]);
+    assert_eq!(GString::from("abc").newlines(), &[]);
+    assert_eq!(GString::from("").newlines(), &[]);
+    assert_eq!(GString::from("\n").newlines(), &[0]);
+    assert_eq!(GString::from("\n\n").newlines(), &[0, 1]);
+    ```
+    */
+    #[must_use]
+    pub fn newlines(&self) -> Vec<usize> {
+        newline_indices(&self.data, self.newline_policy)
+    }
+
+    /**
+    Insert a string at an index
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut s = GString::from("a\u{310}o\u{308}\u{332}");
+    s.insert(1, "e\u{301}");
+
+    assert_eq!(s, S);
+
+    // Clusters spanning the edges of the inserted string are merged
+    let mut s = GString::from("\ne");
+    s.insert(0, "x\r");
+    s.insert(3, "\u{301}");
+
+    assert_eq!(s.graphemes(), &["x", "\r\n", "e\u{301}"]);
+    ```
+
+    See also the [`GString::insert_raw`] method.
+    */
+    pub fn insert(&mut self, index: usize, string: &str) {
+        let _ = self.splice(index..index, string);
+    }
+
+    /**
+    Insert a string at an index without merging clusters at its edges
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("e");
+    s.insert_raw(1, "\u{301}");
+
+    assert_eq!(s.graphemes(), &["e", "\u{301}"]);
+    ```
+
+    See also the [`GString::insert`] method.
+    */
+    pub fn insert_raw(&mut self, index: usize, string: &str) {
+        let _ = self.splice_raw(index..index, string);
+    }
+
+    /**
+    Remove a grapheme at an index
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut s = GString::from(S);
+
+    assert_eq!(s.remove(1), "e\u{301}");
+    assert_eq!(s, "a\u{310}o\u{308}\u{332}");
+
+    // The graphemes on either side are merged if they form a cluster
+    let mut s = GString::from("\rx\n");
+
+    assert_eq!(s.remove(1), "x");
+    assert_eq!(s.graphemes(), &["\r\n"]);
+    ```
+    */
+    pub fn remove(&mut self, index: usize) -> Grapheme {
+        let r = self.data.remove(index);
+        self.resegment(index..index);
+        r
+    }
+
+    /**
+    Append a [`&str`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut s = GString::from("a\u{310}e\u{301}");
+    s.push("o\u{308}\u{332}");
+
+    assert_eq!(s, S);
+
+    // A combining mark merges with the last grapheme
+    let mut s = GString::from("e");
+    s.push("\u{301}");
+
+    assert_eq!(s.graphemes(), &["e\u{301}"]);
+    ```
+
+    See also the [`GString::push_raw`] method.
+    */
+    pub fn push(&mut self, string: &str) {
+        self.append(string);
+    }
+
+    /**
+    Append a [`&str`] without merging clusters with the last grapheme
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("e");
+    s.push_raw("\u{301}");
+
+    assert_eq!(s.graphemes(), &["e", "\u{301}"]);
+    ```
+
+    See also the [`GString::push`] method.
+    */
+    pub fn push_raw(&mut self, string: &str) {
+        self.data.append(&mut graphemes(string));
+        self.shape = calc_shape(&self.data, self.newline_policy);
+    }
+
+    /**
+    Remove the last grapheme and return it as a new [`GString`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut s = GString::from(S);
+
+    assert_eq!(s.pop().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(s, "a\u{310}e\u{301}");
+
+    assert_eq!(s.pop().unwrap(), "e\u{301}");
+    assert_eq!(s, "a\u{310}");
+
+    assert_eq!(s.pop().unwrap(), "a\u{310}");
+    assert_eq!(s, "");
+
+    assert_eq!(s.pop(), None);
+    assert_eq!(s, "");
+
+    let mut s = GString::from("a\n");
+    s.pop();
+
+    assert_eq!(s.shape(), &[0]);
+    ```
+    */
+    pub fn pop(&mut self) -> Option<Grapheme> {
+        let r = self.data.pop();
+        self.shape = calc_shape(&self.data, self.newline_policy);
+        r
+    }
+
+    /**
+    Replace a range with a [`&str`]
+
+    The range can be a `a..b` [`Range<usize>`], `a..` [`RangeFrom<usize>`], `..b`
+    [`RangeTo<usize>`], or `..` [`RangeFull`].
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut s = GString::from(S);
+
+    assert_eq!(s.splice(0..2, "e\u{301}a\u{310}"), "a\u{310}e\u{301}");
+    assert_eq!(s, "e\u{301}a\u{310}o\u{308}\u{332}");
+
+    assert_eq!(s.splice(1.., "o\u{308}\u{332}a\u{310}"), "a\u{310}o\u{308}\u{332}");
+    assert_eq!(s, "e\u{301}o\u{308}\u{332}a\u{310}");
+
+    assert_eq!(s.splice(..1, ""), "e\u{301}");
+    assert_eq!(s, "o\u{308}\u{332}a\u{310}");
+
+    assert_eq!(s.splice(.., ""), "o\u{308}\u{332}a\u{310}");
+    assert_eq!(s, "");
+    ```
+
+    Clusters spanning the edges of the replaced range are merged, so the result always has the
+    same graphemes as a [`GString`] created from the whole string:
+
+    ```
+    use gstring::*;
+
+    // Regional indicators pair up into flags
+    let mut s = GString::from("\u{1f1fa}\u{1f1f8}\u{1f1e9}\u{1f1ea}");
+    assert_eq!(s.len(), 2);
+
+    assert_eq!(s.splice(0..0, "\u{1f1eb}"), "");
+    assert_eq!(s, GString::from(s.to_string().as_str()));
+    assert_eq!(s.len(), 3);
+    ```
+
+    See also the [`GString::splice_raw`] method.
+
+    [`RangeFrom<usize>`]: std::ops::RangeFrom
+
+    [`RangeTo<usize>`]: std::ops::RangeTo
+
+    [`RangeFull`]: std::ops::RangeFull
+    */
+    #[must_use]
+    pub fn splice<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &str) -> GString {
+        self.splice_extent(bounds(range, self.len()), replace_with)
+            .0
+    }
+
+    /**
+    Replace a range like [`GString::splice`] and also return where the re-segmented graphemes end
+    before and after the edit
+
+    Positions at or after the first end are moved to the second end plus their distance past the
+    first end.
+    */
+    fn splice_extent(
+        &mut self,
+        range: Range<usize>,
+        replace_with: &str,
+    ) -> (GString, usize, usize) {
+        let start = range.start;
+        let replace_with = graphemes(replace_with);
+        let end = start + replace_with.len();
+        let data = self
+            .data
+            .splice(range.clone(), replace_with)
+            .collect::<Vec<_>>();
+        let (old_end, new_end) = self.resegment(start..end);
+        (self.with_data(data), range.end + old_end - end, new_end)
+    }
+
+    /**
+    Replace a range with a [`&str`] without merging clusters at the edges of the range
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("ex");
+
+    assert_eq!(s.splice_raw(1.., "\u{301}"), "x");
+    assert_eq!(s.graphemes(), &["e", "\u{301}"]);
+    ```
+
+    See also the [`GString::splice`] method.
+    */
+    #[must_use]
+    pub fn splice_raw<R: RangeBounds<usize>>(&mut self, range: R, replace_with: &str) -> GString {
+        let data = self
+            .data
+            .splice(range, graphemes(replace_with))
+            .collect::<Vec<_>>();
+        self.shape = calc_shape(&self.data, self.newline_policy);
+        self.with_data(data)
+    }
+
+    /**
+    Remove and return a range of graphemes
+
+    The range can be a `a..b` [`Range<usize>`], `a..` [`RangeFrom<usize>`], `..b`
+    [`RangeTo<usize>`], or `..` [`RangeFull`].
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}a\u{310}e\u{301}");
+
+    assert_eq!(s.drain(0..2), "a\u{310}e\u{301}");
+    assert_eq!(s, "o\u{308}\u{332}a\u{310}e\u{301}");
+
+    assert_eq!(s.drain(2..), "e\u{301}");
+    assert_eq!(s, "o\u{308}\u{332}a\u{310}");
+
+    assert_eq!(s.drain(..1), "o\u{308}\u{332}");
+    assert_eq!(s, "a\u{310}");
+
+    assert_eq!(s.drain(..), "a\u{310}");
+    assert_eq!(s, "");
+    ```
+
+    The graphemes on either side of the range are merged if they form a cluster.
+
+    [`RangeFrom<usize>`]: std::ops::RangeFrom
+
+    [`RangeTo<usize>`]: std::ops::RangeTo
+
+    [`RangeFull`]: std::ops::RangeFull
+    */
+    #[must_use]
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> GString {
+        let range = bounds(range, self.len());
+        let start = range.start;
+        let data = self.data.drain(range).collect::<Vec<_>>();
+        self.resegment(start..start);
+        self.with_data(data)
+    }
+
+    /// Re-segment the graphemes around a range with the `resegment` function and update the shape
+    fn resegment(&mut self, range: Range<usize>) -> (usize, usize) {
+        let r = resegment(&mut self.data, range);
+        self.shape = calc_shape(&self.data, self.newline_policy);
+        r
+    }
+
+    /**
+    Create a new [`GString`] with all non-overlapping matches of a pattern replaced with a
+    [`&str`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e e\u{301} e");
+
+    assert_eq!(s.replace("e", "a\u{310}"), "a\u{310} e\u{301} a\u{310}");
+    assert_eq!(s.replace("e\u{301}", "e"), "e e e");
+    assert_eq!(s.replace(|g: &Grapheme| g == " ", ""), "ee\u{301}e");
+    ```
+
+    Note that only whole graphemes are matched, so replacing `"e"` leaves `"e\u{301}"` intact.
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn replace<P: GPattern>(&self, pattern: P, replace_with: &str) -> GString {
+        self.replacen(pattern, replace_with, usize::MAX)
+    }
+
+    /**
+    Create a new [`GString`] with the first `n` non-overlapping matches of a pattern replaced with
+    a [`&str`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e e\u{301} e e");
+
+    assert_eq!(s.replacen("e", "o", 2), "o e\u{301} o e");
+    assert_eq!(s.replacen("e", "o", 0), s);
+    ```
+
+    See also the [`GPattern`] trait.
+    */
+    #[must_use]
+    pub fn replacen<P: GPattern>(&self, pattern: P, replace_with: &str, n: usize) -> GString {
+        let data = replace_ranges(&self.data, self.match_ranges(pattern).take(n), replace_with);
+        self.with_data(data)
+    }
+
+    /**
+    Replace all non-overlapping matches of a pattern with a [`&str`] and return the count of
+    replacements
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("e e\u{301} e");
+
+    assert_eq!(s.replace_all_in_place("e", "a\u{310}"), 2);
+    assert_eq!(s, "a\u{310} e\u{301} a\u{310}");
+    assert_eq!(s.shape(), &[4]);
+    ```
+
+    See also the [`GString::replace`] method and [`GPattern`] trait.
+    */
+    pub fn replace_all_in_place<P: GPattern>(&mut self, pattern: P, replace_with: &str) -> usize {
+        let ranges = self.match_ranges(pattern).collect::<Vec<_>>();
+        let n = ranges.len();
+        if n > 0 {
+            self.data = replace_ranges(&self.data, ranges, replace_with);
+            self.shape = calc_shape(&self.data, self.newline_policy);
+        }
+        n
+    }
+
+    /**
+    Create a new [`GString`] with a range replaced with a [`&str`]
+
+    The range can be a `a..b` [`Range<usize>`], `a..` [`RangeFrom<usize>`], `..b`
+    [`RangeTo<usize>`], or `..` [`RangeFull`].
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(s.replace_range(1..2, "abc"), "a\u{310}abco\u{308}\u{332}");
+    assert_eq!(s.replace_range(..2, ""), "o\u{308}\u{332}");
+    assert_eq!(s, "a\u{310}e\u{301}o\u{308}\u{332}");
+    ```
+
+    See also the [`GString::splice`] method.
+
+    [`RangeFrom<usize>`]: std::ops::RangeFrom
+
+    [`RangeTo<usize>`]: std::ops::RangeTo
+
+    [`RangeFull`]: std::ops::RangeFull
+    */
+    #[must_use]
+    pub fn replace_range<R: RangeBounds<usize>>(&self, range: R, replace_with: &str) -> GString {
+        let mut r = self.clone();
+        let _ = r.splice(range, replace_with);
+        r
+    }
+
+    /**
+    Create a new [`GString`] from an `a..b` [`Range<usize>`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+
+    assert_eq!(s.slice(0..1), "a\u{310}");
+    assert_eq!(s.slice(1..2), "e\u{301}");
+    assert_eq!(s.slice(2..3), "o\u{308}\u{332}");
+    assert_eq!(s.slice(0..2), "a\u{310}e\u{301}");
+    assert_eq!(s.slice(1..3), "e\u{301}o\u{308}\u{332}");
+    assert_eq!(s.slice(0..3), S);
+    ```
+
+    See also the [`GString::index`] method.
+    */
+    #[must_use]
+    pub fn slice(&self, range: Range<usize>) -> GString {
+        let data = self.data[range].to_vec();
+        self.with_data(data)
+    }
+
+    /**
+    Return a reference to the "shape" of the content
+
+    - Length of the shape: Number of lines
+    - Values: Maximum column index for each line (including the newline)
+
+    | Line    | Count | Row Index | Max Column Index |
+    |---------|------:|----------:|-----------------:|
+    | `\n`    |     1 |         0 |                0 |
+    | `a\n`   |     2 |         1 |                1 |
+    | `bc\n`  |     3 |         2 |                2 |
+    | `d\n`   |     4 |         3 |                1 |
+    | `efg\n` |     5 |         4 |                3 |
+    | `\n`    |     6 |         5 |                0 |
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("\na\nbc\nd\nefg\n");
+
+    let shape = s.shape();
+
+    assert_eq!(shape, &[0, 1, 2, 1, 3, 0]);
+
+    // There are 6 lines
+    assert_eq!(shape.len(), 6);
+
+    // Max column index of the 5th line is 3
+    assert_eq!(shape[4], 3);
+    ```
+    */
+    #[must_use]
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /**
+    Generate string showing the row, column, and position for each grapheme
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\nbc\ndef\nghij");
+
+    let d = "  \
+      0 1 2 3 4
+
+      0 1
+    0 a \\n
+      0 1
+
+      0 1 2
+    1 b c \\n
+      2 3 4
+
+      0 1 2 3
+    2 d e f \\n
+      5 6 7 8
+
+      0 1 2 3 4
+    3 g h i j
+      0 1 1 1 1
+      9 0 1 2 3
+
+    ";
+
+    assert_eq!(s.shape_string(), d);
+    ```
+
+    # Notes
+
+    1. There is a column header at the top to show the column header for the longest row.
+    2. Each content row has:
+        - Row column header above
+        - Row index to the left
+        - Position (offset) below
+    3. The last row shows the column and position one grapheme past the end.
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn shape_string(&self) -> String {
+        let mut r = String::new();
+
+        // Column header at top
+        let max_column = *self
+            .shape
+            .iter()
+            .max()
+            .unwrap()
+            .max(&(*self.shape.last().unwrap() + 1));
+        let last_row = self.shape.len() - 1;
+        let row_width = n_digits(last_row);
+        let row_space = " ".repeat(row_width);
+        let e = n_digits(max_column);
+        for n in 0..e {
+            writeln!(
+                r,
+                "{row_space} {}",
+                (0..=max_column)
+                    .map(|x| { format!("{x:0e$}").chars().nth(n).unwrap().to_string() })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+            .unwrap();
+        }
+        writeln!(r).unwrap();
+
+        // Content rows
+        let mut position = 0;
+        for (row, line) in self.lines().iter().enumerate() {
+            // Row column header above
+            let max_column = self.shape[row] + usize::from(row == last_row);
+            let e = n_digits(max_column + 1);
+            for n in 0..e {
+                writeln!(
+                    r,
+                    "{row_space} {}",
+                    (0..=max_column)
+                        .map(|x| { format!("{x:0e$}").chars().nth(n).unwrap().to_string() })
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+                .unwrap();
+            }
+
+            // Content row
+            writeln!(
+                r,
+                "{row:01$} {}",
+                line.iter()
+                    .map(|g| match g.as_str() {
+                        "\n" => "\\n",
+                        "\r\n" => "\\r\\n",
+                        _ => g.as_str(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                row_width,
+            )
+            .unwrap();
+
+            // Position (offset) below
+            let a = position;
+            let b = position + max_column;
+            let e = n_digits(b + 1);
+            for n in 0..e {
+                writeln!(
+                    r,
+                    "{row_space} {}",
+                    (a..=b)
+                        .map(|x| { format!("{x:0e$}").chars().nth(n).unwrap().to_string() })
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+                .unwrap();
+            }
+            writeln!(r).unwrap();
+
+            position += self.shape[row] + 1;
+        }
+        r
+    }
+
+    /**
+    Create a [`GStringRefIter`] for iterating graphemes by reference
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+    let mut i = s.iter();
+
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.next().unwrap(), "e\u{301}");
+    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next(), None);
+    ```
+
+    See also the [`GString::into_iter`][IntoIterator::into_iter] method.
+    */
+    #[must_use]
+    pub fn iter(&self) -> GStringRefIter<'_> {
+        GStringRefIter {
+            iter: self.data.iter(),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Implementations
+
+impl Default for GString {
+    /// Create a new empty [`GString`] with the shape of a single empty line
+    fn default() -> GString {
+        GString {
+            data: vec![],
+            shape: vec![0],
+            newline_policy: NewlinePolicy::default(),
+        }
+    }
+}
+
+impl std::fmt::Display for GString {
+    /**
+    Print a [`GString`] directly in [`print`], [`println`], [`eprint`], [`eprintln`], and [`write`]
+    macros or convert to a [`String`] using the [`format`] macro [`to_string`][ToString::to_string]
+    method
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+
+    assert_eq!(format!("{s}"), S);
+    assert_eq!(format!("{}", s), S);
+    assert_eq!(s.to_string(), S);
+    ```
+    */
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for g in &self.data {
+            write!(f, "{g}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for GString {
+    /**
+    Debug print a [`GString`] in [`format`], [`print`], [`println`], [`write`], [`writeln`], etc
+    macros
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    assert_eq!(
+        format!("{:?}", GString::from(S)),
+        format!("{:?}", S),
+    );
+    ```
+    */
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl<I> Index<I> for GString
+where
+    I: SliceIndex<[Grapheme]>,
+{
+    type Output = I::Output;
+
+    /**
+    Index a slice of [`GString`]'s graphemes with a [`usize`] index, `a..b` [`Range<usize>`], `a..`
+    [`RangeFrom<usize>`], `..b` [`RangeTo<usize>`], or `..` [`RangeFull`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    const G: &[&str] = &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"];
+
+    let s = GString::from(S);
+
+    assert_eq!(&s[0], G[0]);
+    assert_eq!(&s[1], G[1]);
+    assert_eq!(&s[2], G[2]);
+
+    for start in 0..3 {
+        for stop in 1..4 {
+            if stop > start {
+                assert_eq!(&s[start..stop], G[start..stop].to_vec());
+                assert_eq!(&s[..stop], G[..stop].to_vec());
+            }
+        }
+        assert_eq!(&s[start..], G[start..].to_vec());
+    }
+    assert_eq!(&s[..], G);
+    ```
+
+    See also the [`GString::slice`] method.
+
+    [`RangeFrom<usize>`]: std::ops::RangeFrom
+
+    [`RangeTo<usize>`]: std::ops::RangeTo
+
+    [`RangeFull`]: std::ops::RangeFull
+    */
+    fn index(&self, index: I) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl std::cmp::PartialEq<GString> for GString {
+    /**
+    Compare two [`GString`]s
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s1 = GString::from(S);
+    let s2 = GString::from(S);
+    let s3 = GString::from(S);
+
+    assert_eq!(s1, s2);
+    assert_ne!(s3, GString::from(""));
+
+    // The same chars are equal regardless of segmentation
+    let mut s4 = GString::from("e");
+    s4.push_raw("\u{301}");
+
+    assert_eq!(s4.len(), 2);
+    assert_eq!(s4, GString::from("e\u{301}"));
+    ```
+    */
+    fn eq(&self, other: &GString) -> bool {
+        chars(&self.data).eq(chars(&other.data))
+    }
+}
+
+impl std::cmp::PartialEq<GString> for &GString {
+    /**
+    Compare a [`GString`] to a `&`[`GString`] (or two `&`[`GString`]s)
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s1 = GString::from(S);
+    let s2 = GString::from(S);
+    let empty = GString::from("");
+
+    assert_eq!(&s1, s2);
+    assert_ne!(&s1, empty);
+
+    assert_eq!(&s1, &s2);
+    assert_ne!(&s1, &empty);
+    ```
+    */
+    fn eq(&self, other: &GString) -> bool {
+        chars(&self.data).eq(chars(&other.data))
+    }
+}
+
+impl std::cmp::PartialEq<String> for GString {
+    /**
+    Compare a [`GString`] to a [`String`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+
+    assert_eq!(s, S.to_string());
+    assert_ne!(s, String::new());
+    ```
+    */
+    fn eq(&self, other: &String) -> bool {
+        chars(&self.data).eq(other.chars())
+    }
+}
+
+impl std::cmp::PartialEq<&str> for GString {
+    /**
+    Compare a [`GString`] to a [`&str`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+
+    assert_eq!(s, S);
+    assert_ne!(s, "");
+    ```
+    */
+    fn eq(&self, other: &&str) -> bool {
+        chars(&self.data).eq(other.chars())
+    }
+}
+
+impl std::cmp::PartialEq<str> for GString {
+    /**
+    Compare a [`GString`] to a [`str`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(s, "a\u{310}e\u{301}o\u{308}\u{332}");
+    assert_ne!(s, "");
+    ```
+    */
+    fn eq(&self, other: &str) -> bool {
+        chars(&self.data).eq(other.chars())
+    }
+}
+
+impl std::ops::Add<&str> for GString {
+    type Output = GString;
+
+    /**
+    Concatenate a [`GString`] and a [`&str`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}") + "e\u{301}";
+    assert_eq!(s, "a\u{310}e\u{301}");
+    assert_eq!(s.len(), 2);
+
+    // A combining mark merges with the previous grapheme
+    let s = GString::from("cafe") + "\u{301}!";
+    assert_eq!(s.graphemes(), &["c", "a", "f", "e\u{301}", "!"]);
+    ```
+    */
+    fn add(mut self, other: &str) -> GString {
+        self.append(other);
+        self
+    }
+}
+
+impl std::ops::Add<&GString> for GString {
+    type Output = GString;
+
+    /**
+    Concatenate two [`GString`]s
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("\r") + &GString::from("\nabc");
+
+    assert_eq!(s.graphemes(), &["\r\n", "a", "b", "c"]);
+    assert_eq!(s.shape(), &[0, 2]);
+    ```
+    */
+    fn add(mut self, other: &GString) -> GString {
+        self += other;
+        self
+    }
+}
+
+impl std::ops::AddAssign<&str> for GString {
+    /**
+    Append a [`&str`]
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("e");
+    s += "\u{301}";
+
+    assert_eq!(s.graphemes(), &["e\u{301}"]);
+    ```
+    */
+    fn add_assign(&mut self, other: &str) {
+        self.append(other);
+    }
+}
+
+impl std::ops::AddAssign<&GString> for GString {
+    /**
+    Append a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("e");
+    s += &GString::from("\u{301}x");
+
+    assert_eq!(s.graphemes(), &["e\u{301}", "x"]);
+
+    // Merging can cascade past the first grapheme of the other GString
+    let mut s = GString::from("\u{1f1fa}");
+    s += &GString::from("\u{1f1f8}\u{1f1e9}\u{1f1ea}");
+
+    assert_eq!(s.graphemes(), &["\u{1f1fa}\u{1f1f8}", "\u{1f1e9}\u{1f1ea}"]);
+    ```
+
+    Only the graphemes at the join point are re-segmented.
+    */
+    fn add_assign(&mut self, other: &GString) {
+        let n = self.len();
+        self.data.extend_from_slice(&other.data);
+        if n > 0 && !other.is_empty() {
+            self.resegment(n..n + 1);
+        } else {
+            self.shape = calc_shape(&self.data, self.newline_policy);
+        }
+    }
+}
+
+impl std::fmt::Write for GString {
+    /**
+    Append a [`&str`] so that the [`write`] and [`writeln`] macros can be used
+
+    ```
+    use {gstring::*, std::fmt::Write};
+
+    let mut s = GString::new();
+    write!(s, "e").unwrap();
+    writeln!(s, "\u{301} = {}", 1).unwrap();
+
+    assert_eq!(s.graphemes(), &["e\u{301}", " ", "=", " ", "1", "\n"]);
+    assert_eq!(s.shape(), &[5, 0]);
+    ```
+    */
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.append(s);
+        Ok(())
+    }
+}
+
+impl Eq for GString {}
+
+impl std::hash::Hash for GString {
+    /**
+    Hash a [`GString`] by its [`char`]s (ignoring the shape and the newline policy)
+
+    ```
+    use {gstring::*, std::collections::HashSet};
+
+    let mut set = HashSet::new();
+    set.insert(GString::from("a\u{310}e\u{301}"));
+
+    assert!(set.contains(&GString::from("a\u{310}e\u{301}")));
+    assert!(!set.contains(&GString::from("a\u{310}")));
+
+    // Equal regardless of segmentation, so the hashes are equal too
+    let mut s = GString::from("a\u{310}e");
+    s.push_raw("\u{301}");
+
+    assert!(set.contains(&s));
+    ```
+    */
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        hash_chars(&self.data, state);
+    }
+}
+
+impl PartialOrd for GString {
+    fn partial_cmp(&self, other: &GString) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GString {
+    /**
+    Order [`GString`]s by their sequences of [`char`]s (codepoints)
+
+    ```
+    use gstring::*;
+
+    let mut v = vec![
+        GString::from("e\u{301}"),
+        GString::from("b"),
+        GString::from("e"),
+        GString::from("ab"),
+    ];
+    v.sort();
+
+    assert_eq!(v, &["ab", "b", "e", "e\u{301}"]);
+
+    // U+1F600 is greater than U+301
+    assert!(GString::from("e\u{1f600}") > GString::from("e\u{301}"));
+    ```
+
+    This is the same order as the [`String`]s, and differs from ordering the graphemes as a
+    `&[`[`Grapheme`]`]` slice.
+    */
+    fn cmp(&self, other: &GString) -> std::cmp::Ordering {
+        chars(&self.data).cmp(chars(&other.data))
+    }
+}
+
+impl From<&str> for GString {
+    /**
+    Create a new [`GString`] from a [`&str`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+    assert_eq!(s, S);
+
+    let s: GString = S.into();
+    assert_eq!(s, S);
+    ```
+    */
+    fn from(s: &str) -> GString {
+        let data = graphemes(s);
+        GString::from_data(data, NewlinePolicy::default())
+    }
+}
+
+impl From<&String> for GString {
+    /**
+    Create a new [`GString`] from a `&`[`String`]
+
+    ```
+    use gstring::*;
+
+    let s = String::from("a\u{310}e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(GString::from(&s), s);
+    ```
+    */
+    fn from(s: &String) -> GString {
+        GString::from(s.as_str())
+    }
+}
+
+impl From<String> for GString {
+    /**
+    Create a new [`GString`] from a [`String`]
+
+    ```
+    use gstring::*;
+
+    let s = String::from("a\u{310}e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(GString::from(s.clone()), s);
+    ```
+    */
+    fn from(s: String) -> GString {
+        GString::from(s.as_str())
+    }
+}
+
+impl From<Grapheme> for GString {
+    /**
+    Create a new [`GString`] from a [`Grapheme`]
+
+    ```
+    use gstring::*;
+
+    let g = Grapheme::from("a\u{310}").unwrap();
+
+    assert_eq!(GString::from(g), "a\u{310}");
+    ```
+    */
+    fn from(g: Grapheme) -> GString {
+        let data = vec![g];
+        GString::from_data(data, NewlinePolicy::default())
+    }
+}
+
+impl From<GString> for String {
+    /**
+    Convert a [`GString`] into a [`String`]
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s: String = GString::from(S).into();
+
+    assert_eq!(s, S);
+    ```
+    */
+    fn from(s: GString) -> String {
+        s.data.into_iter().map(String::from).collect()
+    }
+}
+
+impl std::str::FromStr for GString {
+    type Err = std::convert::Infallible;
+
+    /**
+    Parse a [`GString`] from a [`&str`]; this never fails
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s: GString = S.parse().unwrap();
+
+    assert_eq!(s, S);
+    ```
+    */
+    fn from_str(s: &str) -> Result<GString, Self::Err> {
+        Ok(GString::from(s))
+    }
+}
+
+impl FromIterator<Grapheme> for GString {
+    /**
+    Collect graphemes into a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+
+    let r: GString = s.graphemes().iter().rev().cloned().collect();
+
+    assert_eq!(r, "o\u{308}\u{332}e\u{301}a\u{310}");
+    ```
+    */
+    fn from_iter<T: IntoIterator<Item = Grapheme>>(iter: T) -> GString {
+        GString::from(iter.into_iter().map(String::from).collect::<String>())
+    }
+}
+
+impl<'a> FromIterator<&'a str> for GString {
+    /**
+    Concatenate [`&str`]s into a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s: GString = ["a", "e", "\u{301}", "\n"].into_iter().collect();
+
+    assert_eq!(s, "ae\u{301}\n");
+    assert_eq!(s.len(), 3);
+    ```
+
+    Note that the strings are concatenated before they are split into graphemes.
+    */
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> GString {
+        GString::from(iter.into_iter().collect::<String>())
+    }
+}
+
+impl FromIterator<GString> for GString {
+    /**
+    Concatenate [`GString`]s into a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s: GString = GString::from("abc\ndef")
+        .lines()
+        .into_iter()
+        .rev()
+        .collect();
+
+    assert_eq!(s, "defabc\n");
+    assert_eq!(s.shape(), &[6, 0]);
+    ```
+    */
+    fn from_iter<T: IntoIterator<Item = GString>>(iter: T) -> GString {
+        iter.into_iter().flatten().collect()
+    }
+}
+
+impl Extend<Grapheme> for GString {
+    /**
+    Append graphemes
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\u{310}");
+    s.extend(GString::from("e\u{301}o\u{308}\u{332}"));
+
+    assert_eq!(s, "a\u{310}e\u{301}o\u{308}\u{332}");
+    ```
+    */
+    fn extend<T: IntoIterator<Item = Grapheme>>(&mut self, iter: T) {
+        self.append(&iter.into_iter().map(String::from).collect::<String>());
+    }
+}
+
+impl<'a> Extend<&'a str> for GString {
+    /**
+    Append [`&str`]s
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\u{310}");
+    s.extend(["e\u{301}", "\n", "o\u{308}\u{332}"]);
+
+    assert_eq!(s, "a\u{310}e\u{301}\no\u{308}\u{332}");
+    assert_eq!(s.shape(), &[2, 0]);
+    ```
+    */
+    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
+        self.push(&iter.into_iter().collect::<String>());
+    }
+}
+
+impl Extend<GString> for GString {
+    /**
+    Append [`GString`]s
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\u{310}");
+    s.extend([GString::from("e\u{301}"), GString::from("o\u{308}\u{332}")]);
+
+    assert_eq!(s, "a\u{310}e\u{301}o\u{308}\u{332}");
+    ```
+    */
+    fn extend<T: IntoIterator<Item = GString>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().flatten());
+    }
+}
+
+impl IntoIterator for GString {
+    type Item = Grapheme;
+    type IntoIter = GStringIter;
+
+    /**
+    Consume the [`GString`] and convert into a [`GStringIter`] for iterating graphemes
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+    let mut i = s.into_iter();
+
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.next().unwrap(), "e\u{301}");
+    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next(), None);
+    ```
+
+    See also the [`GString::iter`] method.
+    */
+    fn into_iter(self) -> GStringIter {
+        GStringIter {
+            iter: self.data.into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a GString {
+    type Item = &'a Grapheme;
+    type IntoIter = GStringRefIter<'a>;
+
+    /**
+    Create a [`GStringRefIter`] for iterating graphemes by reference
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+    let mut n = 0;
+
+    for g in &s {
+        assert_eq!(g.chars().len(), 2 + usize::from(n == 2));
+        n += 1;
+    }
+
+    assert_eq!(n, 3);
+    ```
+
+    See also the [`GString::iter`] method.
+    */
+    fn into_iter(self) -> GStringRefIter<'a> {
+        self.iter()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Created by [`GString::iter`] to iterate graphemes by reference
+pub struct GStringRefIter<'a> {
+    iter: std::slice::Iter<'a, Grapheme>,
+}
+
+impl<'a> Iterator for GStringRefIter<'a> {
+    type Item = &'a Grapheme;
+
+    /**
+    Get the next grapheme by reference
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+    let mut i = s.iter();
+
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.next().unwrap(), "e\u{301}");
+    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next(), None);
+    ```
+    */
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.iter.count()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n)
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        self.iter.last()
+    }
+}
+
+impl DoubleEndedIterator for GStringRefIter<'_> {
+    /**
+    Get the next grapheme from the end by reference
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let s = GString::from(S);
+    let mut i = s.iter();
+
+    assert_eq!(i.len(), 3);
+    assert_eq!(i.next_back().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.len(), 1);
+    assert_eq!(i.next_back().unwrap(), "e\u{301}");
+    assert_eq!(i.next_back(), None);
+    assert_eq!(i.next(), None);
+
+    assert_eq!(
+        s.iter().rev().collect::<Vec<_>>(),
+        &["o\u{308}\u{332}", "e\u{301}", "a\u{310}"],
+    );
+    assert_eq!(s.iter().nth_back(1).unwrap(), "e\u{301}");
+    assert_eq!(s.iter().last().unwrap(), "o\u{308}\u{332}");
+    ```
+    */
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n)
+    }
+}
+
+impl ExactSizeIterator for GStringRefIter<'_> {}
+
+impl std::iter::FusedIterator for GStringRefIter<'_> {}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Created by [`GString::into_iter`][IntoIterator::into_iter] to iterate graphemes
+pub struct GStringIter {
+    iter: std::vec::IntoIter<Grapheme>,
+}
+
+impl Iterator for GStringIter {
+    type Item = Grapheme;
+
+    /**
+    Get the next grapheme
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut i = GString::from(S).into_iter();
+
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.next().unwrap(), "e\u{301}");
+    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next(), None);
+    ```
+
+    Graphemes are moved out of the [`GString`] rather than cloned.
+    */
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.iter.count()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n)
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        self.iter.last()
+    }
+}
+
+impl DoubleEndedIterator for GStringIter {
+    /**
+    Get the next grapheme from the end
+
+    ```
+    use gstring::*;
+
+    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+
+    let mut i = GString::from(S).into_iter();
+
+    assert_eq!(i.len(), 3);
+    assert_eq!(i.next_back().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.len(), 1);
+    assert_eq!(i.next_back().unwrap(), "e\u{301}");
+    assert_eq!(i.next_back(), None);
+    assert_eq!(i.next(), None);
+
+    let r: GString = GString::from(S).into_iter().rev().collect();
+    assert_eq!(r, "o\u{308}\u{332}e\u{301}a\u{310}");
+    ```
+    */
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth_back(n)
+    }
+}
+
+impl ExactSizeIterator for GStringIter {}
+
+impl std::iter::FusedIterator for GStringIter {}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Borrowed view of a sequence of graphemes in a [`GString`]
+
+Created by [`GString::as_gstr`] and the split methods like [`GString::split`].
+
+```
+use gstring::*;
+
+let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+let v = s.as_gstr();
+
+assert_eq!(v, "a\u{310}e\u{301}o\u{308}\u{332}");
+assert_eq!(v.len(), 3);
+assert_eq!(v.graphemes()[1], "e\u{301}");
+assert_eq!(v.to_gstring(), s);
+assert_eq!(format!("{v}"), s.to_string());
+```
+
+Like [`GString`], a [`GStr`] is compared, ordered, and hashed by its [`char`]s.
+*/
+#[derive(Clone, Copy, Default)]
+pub struct GStr<'a> {
+    data: &'a [Grapheme],
+    newline_policy: NewlinePolicy,
+}
+
+impl<'a> GStr<'a> {
+    /**
+    Return a slice reference to the graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+    let v = s.as_gstr();
+
+    assert_eq!(v.graphemes(), &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"]);
+    ```
+    */
+    #[must_use]
+    pub fn graphemes(&self) -> &'a [Grapheme] {
+        self.data
+    }
+
+    /**
+    Return the count of graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+
+    assert_eq!(s.as_gstr().len(), 3);
+    assert_eq!(s.split("e\u{301}").next().unwrap().len(), 1);
+    ```
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /**
+    Return [`true`] if the [`GStr`] has zero graphemes otherwise return [`false`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a,,b");
+    let v = s.split(",").collect::<Vec<_>>();
+
+    assert!(!v[0].is_empty());
+    assert!(v[1].is_empty());
+    assert!(GStr::default().is_empty());
+    ```
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /**
+    Return the [`NewlinePolicy`] of the [`GString`] it was created from
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\rb").with_newline_policy(NewlinePolicy::LfCrlfCr);
+
+    assert_eq!(s.as_gstr().newline_policy(), NewlinePolicy::LfCrlfCr);
+    assert_eq!(GStr::default().newline_policy(), NewlinePolicy::default());
+    ```
+    */
+    #[must_use]
+    pub fn newline_policy(&self) -> NewlinePolicy {
+        self.newline_policy
+    }
+
+    /**
+    Create a new [`GString`] from the graphemes
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("abc,e\u{301}");
+    let v = s.split(",").map(|x| x.to_gstring()).collect::<Vec<_>>();
+
+    assert_eq!(v, &["abc", "e\u{301}"]);
+    assert_eq!(v[1].len(), 1);
+    ```
+    */
+    #[must_use]
+    pub fn to_gstring(&self) -> GString {
+        GString::from(*self)
+    }
+
+    /**
+    Create a [`GStringRefIter`] for iterating graphemes by reference
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+    let mut i = s.as_gstr().iter();
+
+    assert_eq!(i.next().unwrap(), "a\u{310}");
+    assert_eq!(i.next_back().unwrap(), "o\u{308}\u{332}");
+    assert_eq!(i.next().unwrap(), "e\u{301}");
+    assert_eq!(i.next(), None);
+    ```
+    */
+    #[must_use]
+    pub fn iter(&self) -> GStringRefIter<'a> {
+        GStringRefIter {
+            iter: self.data.iter(),
+        }
+    }
+}
+
+impl std::fmt::Display for GStr<'_> {
+    /**
+    Print a [`GStr`] like a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}\no\u{308}\u{332}");
+    let v = s.split("\n").collect::<Vec<_>>();
+
+    assert_eq!(format!("{}", v[0]), "a\u{310}e\u{301}");
+    assert_eq!(v[1].to_string(), "o\u{308}\u{332}");
+    ```
+    */
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for g in self.data {
+            write!(f, "{g}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for GStr<'_> {
+    /**
+    Debug print a [`GStr`] like a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}");
+
+    assert_eq!(format!("{:?}", s.as_gstr()), format!("{s:?}"));
+    assert_eq!(format!("{:?}", GString::from("a\tb").as_gstr()), "\"a\\tb\"");
+    ```
+    */
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl std::cmp::PartialEq for GStr<'_> {
+    /**
+    Compare two [`GStr`]s by their [`char`]s
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("ab,ab,e\u{301}");
+    let v = s.split(",").collect::<Vec<_>>();
+
+    assert_eq!(v[0], v[1]);
+    assert_ne!(v[0], v[2]);
+    ```
+    */
+    fn eq(&self, other: &GStr) -> bool {
+        chars(self.data).eq(chars(other.data))
+    }
+}
+
+impl std::cmp::Eq for GStr<'_> {}
+
+impl std::hash::Hash for GStr<'_> {
+    /**
+    Hash a [`GStr`] by its [`char`]s, like the equal [`GString`]
+
+    ```
+    use {gstring::*, std::collections::HashSet};
+
+    let s = GString::from("ab,e\u{301},ab");
+    let set = s.split(",").collect::<HashSet<_>>();
+
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&GString::from("e\u{301}").as_gstr()));
+    ```
+    */
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        hash_chars(self.data, state);
+    }
+}
+
+impl std::cmp::PartialOrd for GStr<'_> {
+    /// Compare two [`GStr`]s by their [`char`]s
+    fn partial_cmp(&self, other: &GStr) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for GStr<'_> {
+    /**
+    Order [`GStr`]s by their sequences of [`char`]s (codepoints), like [`GString`]s
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("e\u{301},b,e,ab");
+    let mut v = s.split(",").collect::<Vec<_>>();
+    v.sort();
+
+    assert_eq!(v, &["ab", "b", "e", "e\u{301}"]);
+    ```
+    */
+    fn cmp(&self, other: &GStr) -> std::cmp::Ordering {
+        chars(self.data).cmp(chars(other.data))
+    }
+}
+
+impl std::cmp::PartialEq<&str> for GStr<'_> {
+    /**
+    Compare a [`GStr`] to a [`&str`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}");
+
+    assert_eq!(s.as_gstr(), "a\u{310}e\u{301}");
+    assert_ne!(s.as_gstr(), "a\u{310}");
+    ```
+    */
+    fn eq(&self, other: &&str) -> bool {
+        chars(self.data).eq(other.chars())
+    }
+}
+
+impl std::cmp::PartialEq<str> for GStr<'_> {
+    /**
+    Compare a [`GStr`] to a [`str`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}");
+
+    assert!(s.as_gstr() == *"a\u{310}e\u{301}");
+    assert!(s.as_gstr() != *"e\u{301}");
+    ```
+    */
+    fn eq(&self, other: &str) -> bool {
+        chars(self.data).eq(other.chars())
+    }
+}
+
+impl std::cmp::PartialEq<GString> for GStr<'_> {
+    /**
+    Compare a [`GStr`] to a [`GString`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("ab,e\u{301}");
+    let v = s.split(",").collect::<Vec<_>>();
+
+    assert_eq!(v[1], GString::from("e\u{301}"));
+    assert_ne!(v[0], s);
+    ```
+    */
+    fn eq(&self, other: &GString) -> bool {
+        chars(self.data).eq(chars(&other.data))
+    }
+}
+
+impl<'a> IntoIterator for GStr<'a> {
+    type Item = &'a Grapheme;
+    type IntoIter = GStringRefIter<'a>;
+
+    /**
+    Create a [`GStringRefIter`] for iterating graphemes by reference
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}");
+    let mut n = 0;
+
+    for g in s.as_gstr() {
+        assert_eq!(g.chars().len(), 2);
+        n += 1;
+    }
+
+    assert_eq!(n, 2);
+    ```
+    */
+    fn into_iter(self) -> GStringRefIter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &GStr<'a> {
+    type Item = &'a Grapheme;
+    type IntoIter = GStringRefIter<'a>;
+
+    /**
+    Create a [`GStringRefIter`] for iterating graphemes by reference
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}");
+    let v = s.as_gstr();
+
+    assert_eq!((&v).into_iter().count(), 2);
+    assert_eq!(v.len(), 2);
+    ```
+    */
+    fn into_iter(self) -> GStringRefIter<'a> {
+        self.iter()
+    }
+}
+
+impl From<GStr<'_>> for GString {
+    /**
+    Create a new [`GString`] from a [`GStr`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("abc\ne\u{301}");
+    let v = s.split("\n").map(GString::from).collect::<Vec<_>>();
+
+    assert_eq!(v, &["abc", "e\u{301}"]);
+    assert_eq!(v[0].lines().len(), 1);
+
+    // The newline policy is carried over
+    let s = GString::from("a\rb").with_newline_policy(NewlinePolicy::LfCrlfCr);
+    let t = GString::from(s.as_gstr());
+
+    assert_eq!(t.newline_policy(), NewlinePolicy::LfCrlfCr);
+    assert_eq!(t.lines(), &["a\r", "b"]);
+    ```
+    */
+    fn from(s: GStr<'_>) -> GString {
+        let data = s.data.to_vec();
+        GString::from_data(data, s.newline_policy)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+A selection over [`GString`] positions with an anchor (where the selection started) and a head
+(where the cursor is)
+
+```
+use gstring::*;
+
+let s = Selection::new(5, 2);
+
+assert_eq!(s.start(), 2);
+assert_eq!(s.end(), 5);
+assert_eq!(s.range(), 2..5);
+assert_eq!(s.len(), 3);
+assert!(s.is_reversed());
+
+let c = Selection::cursor(3);
+
+assert!(c.is_empty());
+assert_eq!(c.range(), 3..3);
+```
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Selection {
+    /// Position where the selection started
+    pub anchor: usize,
+
+    /// Position of the cursor
+    pub head: usize,
+}
+
+impl Selection {
+    /**
+    Create a new [`Selection`] from an anchor and a head position
+
+    ```
+    use gstring::*;
+
+    let s = Selection::new(1, 4);
+
+    assert_eq!(s.anchor, 1);
+    assert_eq!(s.head, 4);
+    ```
+
+    The head can be before the anchor (see [`Selection::is_reversed`]).
+    */
+    #[must_use]
+    pub fn new(anchor: usize, head: usize) -> Selection {
+        Selection { anchor, head }
+    }
+
+    /**
+    Create a new empty [`Selection`] (a cursor) at a position
+
+    ```
+    use gstring::*;
+
+    let c = Selection::cursor(2);
+
+    assert_eq!(c, Selection::new(2, 2));
+    assert!(c.is_empty());
+    ```
+    */
+    #[must_use]
+    pub fn cursor(position: usize) -> Selection {
+        Selection::new(position, position)
+    }
+
+    /**
+    Return the lesser of the anchor and head positions
+
+    ```
+    use gstring::*;
+
+    assert_eq!(Selection::new(1, 4).start(), 1);
+    assert_eq!(Selection::new(4, 1).start(), 1);
+    ```
+    */
+    #[must_use]
+    pub fn start(&self) -> usize {
+        self.anchor.min(self.head)
+    }
+
+    /**
+    Return the greater of the anchor and head positions
+
+    ```
+    use gstring::*;
+
+    assert_eq!(Selection::new(1, 4).end(), 4);
+    assert_eq!(Selection::new(4, 1).end(), 4);
+    ```
+    */
+    #[must_use]
+    pub fn end(&self) -> usize {
+        self.anchor.max(self.head)
+    }
+
+    /**
+    Return the selected `a..b` [`Range<usize>`]
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+    let sel = Selection::new(3, 1);
+
+    assert_eq!(sel.range(), 1..3);
+    assert_eq!(s.slice(sel.range()), "e\u{301}o\u{308}\u{332}");
+    ```
+    */
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.start()..self.end()
+    }
+
+    /**
+    Return the count of selected graphemes
+
+    ```
+    use gstring::*;
+
+    assert_eq!(Selection::new(1, 4).len(), 3);
+    assert_eq!(Selection::new(4, 1).len(), 3);
+    assert_eq!(Selection::cursor(2).len(), 0);
+    ```
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end() - self.start()
+    }
+
+    /**
+    Return [`true`] if the [`Selection`] is a cursor (selects zero graphemes)
+
+    ```
+    use gstring::*;
+
+    assert!(Selection::cursor(2).is_empty());
+    assert!(!Selection::new(1, 4).is_empty());
+    ```
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+
+    /**
+    Return [`true`] if the head is before the anchor
+
+    ```
+    use gstring::*;
+
+    assert!(Selection::new(4, 1).is_reversed());
+    assert!(!Selection::new(1, 4).is_reversed());
+    assert!(!Selection::cursor(2).is_reversed());
+    ```
+    */
+    #[must_use]
+    pub fn is_reversed(&self) -> bool {
+        self.head < self.anchor
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Multiple [`Selection`]s over the same [`GString`] that are edited simultaneously
+
+Selections are kept sorted by position and overlapping selections are merged.
+Each edit is applied via [`GString::splice`] from the first selection to the last and the positions
+of later selections are adjusted as earlier ones change length.
+Positions inside graphemes that an edit merged with its neighbors move to the end of the merged
+graphemes, and selections that meet are merged.
+
+```
+use gstring::*;
+
+let mut s = GString::from("a\u{310} e\u{301} o\u{308}\u{332}");
+let mut m = MultiSelection::new(vec![
+    Selection::cursor(0),
+    Selection::cursor(2),
+    Selection::cursor(4),
+]);
+
+m.insert(&mut s, "[").unwrap();
+assert_eq!(s, "[a\u{310} [e\u{301} [o\u{308}\u{332}");
+assert_eq!(m.selections(), &[
+    Selection::cursor(1),
+    Selection::cursor(4),
+    Selection::cursor(7),
+]);
+```
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct MultiSelection {
+    selections: Vec<Selection>,
+}
+
+impl MultiSelection {
+    /**
+    Create a new [`MultiSelection`]
+
+    ```
+    use gstring::*;
+
+    let m = MultiSelection::new(vec![
+        Selection::new(6, 4),
+        Selection::new(0, 2),
+        Selection::new(1, 3),
+        Selection::cursor(8),
+        Selection::cursor(8),
+    ]);
+
+    assert_eq!(m.selections(), &[
+        Selection::new(0, 3),
+        Selection::new(6, 4),
+        Selection::cursor(8),
+    ]);
+    ```
+
+    Note that the selections are sorted and overlapping selections (or duplicate cursors) are
+    merged.
+    */
+    #[must_use]
+    pub fn new(mut selections: Vec<Selection>) -> MultiSelection {
+        selections.sort_by_key(|s| (s.start(), s.end()));
+        let mut merged: Vec<Selection> = vec![];
+        for s in selections {
+            match merged.last_mut() {
+                Some(prev)
+                    if s.start() < prev.end() || (s.is_empty() && s.start() == prev.end()) =>
+                {
+                    let (start, end) = (prev.start(), prev.end().max(s.end()));
+                    *prev = if prev.is_reversed() {
+                        Selection::new(end, start)
+                    } else {
+                        Selection::new(start, end)
+                    };
+                }
+                _ => merged.push(s),
+            }
+        }
+        MultiSelection { selections: merged }
+    }
+
+    /**
+    Return a slice reference to the selections
+
+    ```
+    use gstring::*;
+
+    let m = MultiSelection::new(vec![Selection::cursor(4), Selection::new(0, 2)]);
+
+    assert_eq!(m.selections(), &[Selection::new(0, 2), Selection::cursor(4)]);
+    ```
+    */
+    #[must_use]
+    pub fn selections(&self) -> &[Selection] {
+        &self.selections
+    }
+
+    /**
+    Return the count of selections
+
+    ```
+    use gstring::*;
+
+    let m = MultiSelection::new(vec![Selection::cursor(0), Selection::cursor(2)]);
+    assert_eq!(m.len(), 2);
+
+    // Duplicate cursors are merged
+    let m = MultiSelection::new(vec![Selection::cursor(1), Selection::cursor(1)]);
+    assert_eq!(m.len(), 1);
+    ```
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.selections.len()
+    }
+
+    /**
+    Return [`true`] if there are zero selections
+
+    ```
+    use gstring::*;
+
+    assert!(MultiSelection::default().is_empty());
+    assert!(!MultiSelection::new(vec![Selection::cursor(0)]).is_empty());
+    ```
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.selections.is_empty()
+    }
+
+    /**
+    Insert a [`&str`] at the head of every selection
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc def");
+    let mut m = MultiSelection::new(vec![Selection::new(0, 3), Selection::new(7, 4)]);
+
+    m.insert(&mut s, "e\u{301}").unwrap();
+
+    assert_eq!(s, "abce\u{301} e\u{301}def");
+    assert_eq!(m.selections(), &[Selection::new(0, 4), Selection::new(9, 6)]);
+    ```
+
+    Selections are extended to include text inserted at their end and shifted past text inserted
+    at their start.
+
+    Inserted text can merge with neighboring graphemes (see [`GString::splice`]), which can shorten
+    the [`GString`]; the head is then placed after the merged graphemes:
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("\u{1f468}\u{1f469}");
+    let mut m = MultiSelection::new(vec![Selection::cursor(1)]);
+
+    m.insert(&mut s, "\u{200d}").unwrap();
+
+    assert_eq!(s.graphemes(), &["\u{1f468}\u{200d}\u{1f469}"]);
+    assert_eq!(m.selections(), &[Selection::cursor(1)]);
+    ```
+
+    # Errors
+
+    Returns an error if any selection is out of bounds
+    */
+    pub fn insert(&mut self, gstring: &mut GString, string: &str) -> Result<()> {
+        self.check(gstring)?;
+        let (mut old, mut new) = (0, 0);
+        for s in &mut self.selections {
+            let (anchor, head) = (shift(s.anchor, old, new), shift(s.head, old, new));
+            let (_, old_end, new_end) = gstring.splice_extent(head..head, string);
+            (old, new) = (old + old_end - new, new_end);
+            let anchor = if s.anchor < s.head {
+                anchor
+            } else {
+                shift(s.anchor, old, new)
+            };
+            *s = Selection::new(anchor, shift(s.head, old, new));
+        }
+        *self = MultiSelection::new(std::mem::take(&mut self.selections));
+        Ok(())
+    }
+
+    /**
+    Delete the graphemes in every selection and return them
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}a\u{310}e\u{301}");
+    let mut m = MultiSelection::new(vec![Selection::new(0, 1), Selection::new(2, 4)]);
+
+    assert_eq!(m.delete(&mut s).unwrap(), &["a\u{310}", "o\u{308}\u{332}a\u{310}"]);
+    assert_eq!(s, "e\u{301}e\u{301}");
+    assert_eq!(m.selections(), &[Selection::cursor(0), Selection::cursor(1)]);
+    ```
+
+    Each selection becomes a cursor where its graphemes were removed.
+
+    The graphemes around a deletion can merge (see [`GString::splice`]), which can remove more
+    than the selected graphemes from the length; the cursor is then placed after the merged
+    graphemes:
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("\rx\n");
+    let mut m = MultiSelection::new(vec![Selection::new(1, 2)]);
+
+    assert_eq!(m.delete(&mut s).unwrap(), &["x"]);
+    assert_eq!(s.graphemes(), &["\r\n"]);
+    assert_eq!(m.selections(), &[Selection::cursor(1)]);
+    ```
+
+    # Errors
+
+    Returns an error if any selection is out of bounds
+    */
+    pub fn delete(&mut self, gstring: &mut GString) -> Result<Vec<GString>> {
+        self.replace(gstring, "")
+    }
+
+    /**
+    Replace the graphemes in every selection with a [`&str`] and return the removed graphemes
+
+    ```
+    use gstring::*;
+
+    let mut s = GString::from("abc abc abc");
+    let mut m = MultiSelection::new(vec![
+        Selection::new(0, 3),
+        Selection::new(4, 7),
+        Selection::new(11, 8),
+    ]);
+
+    assert_eq!(m.replace(&mut s, "e\u{301}").unwrap(), &["abc", "abc", "abc"]);
+    assert_eq!(s, "e\u{301} e\u{301} e\u{301}");
+    assert_eq!(m.selections(), &[
+        Selection::cursor(1),
+        Selection::cursor(3),
+        Selection::cursor(5),
+    ]);
+    ```
+
+    Each selection becomes a cursor after its replacement.
+
+    # Errors
+
+    Returns an error if any selection is out of bounds
+    */
+    pub fn replace(&mut self, gstring: &mut GString, replace_with: &str) -> Result<Vec<GString>> {
+        self.check(gstring)?;
+        let mut r = vec![];
+        let (mut old, mut new) = (0, 0);
+        for s in &mut self.selections {
+            let range = shift(s.start(), old, new)..shift(s.end(), old, new);
+            let (removed, old_end, new_end) = gstring.splice_extent(range, replace_with);
+            r.push(removed);
+            (old, new) = (old + old_end - new, new_end);
+            *s = Selection::cursor(shift(s.end(), old, new));
+        }
+        *self = MultiSelection::new(std::mem::take(&mut self.selections));
+        Ok(r)
+    }
+
+    /// Return an error if any selection is out of bounds
+    fn check(&self, gstring: &GString) -> Result<()> {
+        match self.selections.last() {
+            Some(s) if s.end() > gstring.len() => Err(anyhow!(
+                "Selection {:?} is out of bounds for length {}",
+                s.range(),
+                gstring.len(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Regex
+
+/**
+Regular expression search over a [`GString`] (requires the `regex` feature)
+
+All ranges are grapheme index ranges.
+Matches that would split a grapheme are rejected and the search continues at the next grapheme
+boundary.
+Capture groups of an accepted match are snapped outward to the nearest grapheme boundaries.
+*/
+#[cfg(feature = "regex")]
+impl GString {
+    /**
+    Return the range of the first regex match
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("e\u{301}e e\u{301}");
+
+    assert_eq!(s.regex_find(&Regex::new(r"e\s").unwrap()), Some(1..3));
+
+    // Would split the first grapheme so it is rejected
+    assert_eq!(s.regex_find(&Regex::new("e").unwrap()), Some(1..2));
+    ```
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn regex_find(&self, regex: &Regex) -> Option<Range<usize>> {
+        let s = self.to_string();
+        let offsets = byte_offsets(&self.data);
+        regex_captures(regex, &s, &offsets, 1)
+            .first()
+            .map(|c| grapheme_range(&offsets, &c.get(0).unwrap().range()))
+    }
+
+    /**
+    Return the ranges of all non-overlapping regex matches
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("a\u{310}bc e\u{301}f");
+
+    assert_eq!(s.regex_find_all(&Regex::new(r"\S+").unwrap()), &[0..3, 4..6]);
+    assert_eq!(s.regex_find_all(&Regex::new(r"\w").unwrap()), &[1..2, 2..3, 5..6]);
+    ```
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn regex_find_all(&self, regex: &Regex) -> Vec<Range<usize>> {
+        let s = self.to_string();
+        let offsets = byte_offsets(&self.data);
+        regex_captures(regex, &s, &offsets, usize::MAX)
+            .iter()
+            .map(|c| grapheme_range(&offsets, &c.get(0).unwrap().range()))
+            .collect()
+    }
+
+    /**
+    Return the capture groups of the first regex match
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("name: Jose\u{301}");
+    let re = Regex::new(r"(?<key>\w+): (?<value>.*)").unwrap();
+
+    let c = s.regex_captures(&re).unwrap();
+
+    assert_eq!(c.len(), 3);
+    assert_eq!(c.get(0), Some(0..10));
+    assert_eq!(c.name("key"), Some(0..4));
+    assert_eq!(c.name("value"), Some(6..10));
+    assert_eq!(s.slice(c.name("value").unwrap()), "Jose\u{301}");
+
+    // The group matches only the "e" so it is snapped to the whole grapheme
+    let c = s.regex_captures(&Regex::new(r"J\w+(e)\p{M}").unwrap()).unwrap();
+
+    assert_eq!(c.get(1), Some(9..10));
+    ```
+    */
+    #[must_use]
+    pub fn regex_captures(&self, regex: &Regex) -> Option<RegexCaptures> {
+        let s = self.to_string();
+        let offsets = byte_offsets(&self.data);
+        regex_captures(regex, &s, &offsets, 1)
+            .first()
+            .map(|c| RegexCaptures {
+                groups: c
+                    .iter()
+                    .map(|m| m.map(|m| grapheme_range(&offsets, &m.range())))
+                    .collect(),
+                names: regex
+                    .capture_names()
+                    .enumerate()
+                    .filter_map(|(i, name)| name.map(|name| (name.to_string(), i)))
+                    .collect(),
+            })
+    }
+
+    /**
+    Create a new [`GString`] with all non-overlapping regex matches replaced
+
+    The replacement can refer to capture groups with `$1`, `$name`, etc, as in
+    [`Regex::replace_all`].
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("e\u{301}e e\u{301}");
+
+    assert_eq!(
+        s.regex_replace(&Regex::new(r"(e)(\s)").unwrap(), "[$1]$2"),
+        "e\u{301}[e] e\u{301}",
+    );
+    assert_eq!(s.regex_replace(&Regex::new("e").unwrap(), "o"), "e\u{301}o e\u{301}");
+    ```
+    */
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn regex_replace(&self, regex: &Regex, replacement: &str) -> GString {
+        let s = self.to_string();
+        let offsets = byte_offsets(&self.data);
+        let mut r = String::new();
+        let mut position = 0;
+        for c in regex_captures(regex, &s, &offsets, usize::MAX) {
+            let m = c.get(0).unwrap();
+            r.push_str(&s[position..m.start()]);
+            c.expand(replacement, &mut r);
+            position = m.end();
+        }
+        r.push_str(&s[position..]);
+        self.with_data(graphemes(&r))
+    }
+}
+
+/// Created by [`GString::regex_captures`] to hold the grapheme index ranges of capture groups
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegexCaptures {
+    groups: Vec<Option<Range<usize>>>,
+    names: HashMap<String, usize>,
+}
+
+#[cfg(feature = "regex")]
+impl RegexCaptures {
+    /**
+    Return the grapheme index range of a capture group by index (`0` is the whole match)
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("a\u{310}b = e\u{301}f");
+    let c = s.regex_captures(&Regex::new(r"(\w+) = (\w+)").unwrap()).unwrap();
+
+    assert_eq!(c.get(0), Some(0..7));
+    assert_eq!(c.get(1), Some(0..2));
+    assert_eq!(c.get(2), Some(5..7));
+    assert_eq!(c.get(3), None);
+
+    // A match of the "e" in "e\u{301}" would split the grapheme, so it is rejected and the
+    // search continues at the next grapheme boundary
+    let s = GString::from("e\u{301} e");
+    let c = s.regex_captures(&Regex::new(r"(e)").unwrap()).unwrap();
+
+    assert_eq!(c.get(1), Some(2..3));
+    ```
+
+    Returns [`None`] if the index is out of range or the group did not participate in the match:
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("e\u{301}");
+    let c = s.regex_captures(&Regex::new(r"(x)?(e\p{M})").unwrap()).unwrap();
+
+    assert_eq!(c.get(1), None);
+    assert_eq!(c.get(2), Some(0..1));
+    ```
+    */
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Range<usize>> {
+        self.groups.get(index).cloned().flatten()
+    }
+
+    /**
+    Return the grapheme index range of a named capture group
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("o\u{308}\u{332}=a\u{310}");
+    let re = Regex::new(r"(?<key>\w+)=(?<value>\w+)").unwrap();
+    let c = s.regex_captures(&re).unwrap();
+
+    assert_eq!(c.name("key"), Some(0..1));
+    assert_eq!(c.name("value"), Some(2..3));
+    assert_eq!(c.name("other"), None);
+    ```
+    */
+    #[must_use]
+    pub fn name(&self, name: &str) -> Option<Range<usize>> {
+        self.get(*self.names.get(name)?)
+    }
+
+    /**
+    Return the count of capture groups (including the whole match)
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("a\u{310}b");
+
+    let c = s.regex_captures(&Regex::new(r"(\w)(x)?").unwrap()).unwrap();
+    assert_eq!(c.len(), 3);
+
+    let c = s.regex_captures(&Regex::new(r"\w").unwrap()).unwrap();
+    assert_eq!(c.len(), 1);
+    ```
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /**
+    Return [`true`] if there are zero capture groups
+
+    ```
+    use {gstring::*, regex::Regex};
+
+    let s = GString::from("e\u{301}");
+    let c = s.regex_captures(&Regex::new(r"\w\p{M}").unwrap()).unwrap();
+
+    // The whole match is always a capture group
+    assert!(!c.is_empty());
+    ```
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Options for the [`GString::find_with`], [`GString::rfind_with`] and [`GString::match_ranges_with`]
+methods
+
+Graphemes are compared one-to-one, so positions in the result always refer to the original
+[`GString`].
+
+Case-insensitive comparison uses full case folding, the same as [`GString::case_fold`]:
+
+```
+use gstring::*;
+
+let options = FindOptions {
+    case_insensitive: true,
+    ..Default::default()
+};
+
+// Final sigma folds to sigma
+assert_eq!(GString::from("ΣΑΣ").find_with("σας", options), Some(0));
+
+// "ß" and "ẞ" fold to "ss" but are one grapheme, so they do not match the two graphemes "SS"
+assert_eq!(GString::from("stra\u{df}e").find_with("STRA\u{1e9e}E", options), Some(0));
+assert_eq!(GString::from("stra\u{df}e").find_with("SS", options), None);
+```
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    /// Compare graphemes after case folding
+    pub case_insensitive: bool,
+
+    /// Compare graphemes after removing combining marks from their canonical decomposition (NFD)
+    pub diacritic_insensitive: bool,
+
+    /// Compare the canonical decompositions (NFD) of graphemes, so canonically equivalent
+    /// graphemes are equal
+    pub canonical: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Options for the [`GString::sort_lines`] method
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    /// Compare lines after case folding
+    pub case_insensitive: bool,
+
+    /// Compare runs of ASCII digits by their numeric value, so `"a9"` sorts before `"a10"`
+    pub natural: bool,
+}
+
+/// Sort key comparing runs of ASCII digits by their numeric value
+#[derive(PartialEq, Eq)]
+struct NaturalKey(String);
+
+impl PartialOrd for NaturalKey {
+    fn partial_cmp(&self, other: &NaturalKey) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NaturalKey {
+    /// Compare numerically, falling back to the [`String`]s so that equal numbers with different
+    /// leading zeros (e.g. `"a01"` and `"a1"`) still have a consistent order
+    fn cmp(&self, other: &NaturalKey) -> std::cmp::Ordering {
+        self.natural_cmp(other).then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl NaturalKey {
+    /// Compare runs of ASCII digits by their numeric value and other [`char`]s by codepoint
+    fn natural_cmp(&self, other: &NaturalKey) -> std::cmp::Ordering {
+        use std::cmp::Ordering::Equal;
+        let (mut left, mut right) = (self.0.as_str(), other.0.as_str());
+        loop {
+            match (left.chars().next(), right.chars().next()) {
+                (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                    let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                    let (l_end, r_end) = (digits(left), digits(right));
+                    let l_num = left[..l_end].trim_start_matches('0');
+                    let r_num = right[..r_end].trim_start_matches('0');
+                    match l_num.len().cmp(&r_num.len()).then_with(|| l_num.cmp(r_num)) {
+                        Equal => (left, right) = (&left[l_end..], &right[r_end..]),
+                        ordering => return ordering,
+                    }
+                }
+                (Some(l), Some(r)) if l == r => {
+                    (left, right) = (&left[l.len_utf8()..], &right[r.len_utf8()..]);
+                }
+                (l, r) => return l.cmp(&r),
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Case conversions for the [`GString::convert_case`] method
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Casing {
+    /// Uppercase, see [`GString::to_uppercase`]
+    Upper,
+
+    /// Lowercase, see [`GString::to_lowercase`]
+    Lower,
+
+    /// Title case, see [`GString::to_titlecase`]
+    Title,
+
+    /// Case folding, see [`GString::case_fold`]
+    Fold,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Set of graphemes that end a line, see [`GString::set_newline_policy`] and [`IsNewline`]
+
+Note that `"\r\n"` is a single grapheme, so it is always a single newline.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NewlinePolicy {
+    /// Line feed (`"\n"`) and carriage return + line feed (`"\r\n"`)
+    #[default]
+    LfCrlf,
+
+    /// Line feed, carriage return + line feed, and carriage return (`"\r"`)
+    LfCrlfCr,
+
+    /// All Unicode line terminators: line feed, carriage return + line feed, carriage return, line
+    /// tabulation (U+000B), form feed (U+000C), next line (U+0085), line separator (U+2028) and
+    /// paragraph separator (U+2029)
+    Unicode,
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Kinds of line ending graphemes, see [`GString::normalize_line_endings`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LineEnding {
+    /// Line feed (`"\n"`)
+    Lf,
+
+    /// Carriage return + line feed (`"\r\n"`)
+    Crlf,
+
+    /// Carriage return (`"\r"`)
+    Cr,
+
+    /// Line tabulation (U+000B)
+    Vt,
+
+    /// Form feed (U+000C)
+    Ff,
+
+    /// Next line (U+0085)
+    Nel,
+
+    /// Line separator (U+2028)
+    Ls,
+
+    /// Paragraph separator (U+2029)
+    Ps,
+}
+
+impl LineEnding {
+    /**
+    All line endings in order
 
-    assert_eq!(format!("{s}"), S);
-    assert_eq!(format!("{}", s), S);
-    assert_eq!(s.to_string(), S);
+    ```
+    use gstring::*;
+
+    assert_eq!(LineEnding::ALL.len(), 8);
+    assert_eq!(LineEnding::ALL[0], LineEnding::Lf);
+    assert!(LineEnding::ALL.is_sorted());
     ```
     */
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for g in &self.data {
-            write!(f, "{g}")?;
+    pub const ALL: [LineEnding; 8] = [
+        LineEnding::Lf,
+        LineEnding::Crlf,
+        LineEnding::Cr,
+        LineEnding::Vt,
+        LineEnding::Ff,
+        LineEnding::Nel,
+        LineEnding::Ls,
+        LineEnding::Ps,
+    ];
+
+    /**
+    Return the line ending as a [`&str`]
+
+    ```
+    use gstring::*;
+
+    assert_eq!(LineEnding::Crlf.as_str(), "\r\n");
+    assert_eq!(LineEnding::Ls.as_str(), "\u{2028}");
+    ```
+    */
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+            LineEnding::Vt => "\u{b}",
+            LineEnding::Ff => "\u{c}",
+            LineEnding::Nel => "\u{85}",
+            LineEnding::Ls => "\u{2028}",
+            LineEnding::Ps => "\u{2029}",
         }
-        Ok(())
+    }
+
+    /**
+    Return the line ending for a [`Grapheme`] or [`None`] if it is not a line ending
+
+    ```
+    use gstring::*;
+
+    let s = GString::from("a\r\nb\u{85}");
+
+    assert_eq!(LineEnding::from_grapheme(&s[1]), Some(LineEnding::Crlf));
+    assert_eq!(LineEnding::from_grapheme(&s[3]), Some(LineEnding::Nel));
+    assert_eq!(LineEnding::from_grapheme(&s[0]), None);
+    ```
+
+    Recognizes every kind of line ending regardless of the [`NewlinePolicy`].
+    */
+    #[must_use]
+    pub fn from_grapheme(g: &Grapheme) -> Option<LineEnding> {
+        LineEnding::ALL.into_iter().find(|e| g == e.as_str())
     }
 }
 
-impl std::fmt::Debug for GString {
+//--------------------------------------------------------------------------------------------------
+
+/// Counts of each kind of line ending, created by [`GString::line_ending_stats`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LineEndingStats {
+    counts: [usize; 8],
+}
+
+impl LineEndingStats {
     /**
-    Debug print a [`GString`] in [`format`], [`print`], [`println`], [`write`], [`writeln`], etc
-    macros
+    Return the count of a kind of line ending
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    let stats = GString::from("a\nb\r\nc\n").line_ending_stats();
 
-    assert_eq!(
-        format!("{:?}", GString::from(S)),
-        format!("{:?}", S),
-    );
+    assert_eq!(stats.get(LineEnding::Lf), 2);
+    assert_eq!(stats.get(LineEnding::Crlf), 1);
+    assert_eq!(stats.get(LineEnding::Cr), 0);
     ```
     */
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self.to_string())
+    #[must_use]
+    pub fn get(&self, line_ending: LineEnding) -> usize {
+        self.counts[line_ending as usize]
+    }
+
+    /**
+    Return the count of all line endings
+
+    ```
+    use gstring::*;
+
+    assert_eq!(GString::from("a\nb\r\nc\u{2028}").line_ending_stats().total(), 3);
+    assert_eq!(GString::from("abc").line_ending_stats().total(), 0);
+    ```
+    */
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /**
+    Return the most frequent line ending or [`None`] if there are no line endings
+
+    ```
+    use gstring::*;
+
+    let stats = GString::from("a\r\nb\nc\r\n").line_ending_stats();
+
+    assert_eq!(stats.dominant(), Some(LineEnding::Crlf));
+
+    // Ties go to the line ending that comes first in `LineEnding::ALL`
+    let stats = GString::from("a\rb\n").line_ending_stats();
+
+    assert_eq!(stats.dominant(), Some(LineEnding::Lf));
+    assert_eq!(GString::from("abc").line_ending_stats().dominant(), None);
+    ```
+    */
+    #[must_use]
+    pub fn dominant(&self) -> Option<LineEnding> {
+        self.dominant_by(|_| true)
+    }
+
+    /// Return the most frequent line ending accepted by a filter
+    fn dominant_by(&self, f: impl Fn(LineEnding) -> bool) -> Option<LineEnding> {
+        LineEnding::ALL
+            .into_iter()
+            .rev()
+            .filter(|e| self.get(*e) > 0 && f(*e))
+            .max_by_key(|e| self.get(*e))
     }
 }
 
-impl<I> Index<I> for GString
-where
-    I: SliceIndex<[Grapheme]>,
-{
-    type Output = I::Output;
+//--------------------------------------------------------------------------------------------------
+
+/**
+Map from the grapheme positions of an original [`GString`] to the positions of a converted
+[`GString`]
 
+Each original grapheme maps to a (possibly empty) range of converted graphemes.
+
+```
+use gstring::*;
+
+let (upper, map) = GString::from("a\u{df}c").convert_case(Casing::Upper);
+
+assert_eq!(upper, "ASSC");
+assert_eq!(map.ranges(), &[0..1, 1..3, 3..4]);
+```
+
+See the [`GString::convert_case`] and [`GString::normalize_line_endings`] methods.
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PositionMap {
+    ranges: Vec<Range<usize>>,
+    len: usize,
+}
+
+impl PositionMap {
     /**
-    Index a slice of [`GString`]'s graphemes with a [`usize`] index, `a..b` [`Range<usize>`], `a..`
-    [`RangeFrom<usize>`], `..b` [`RangeTo<usize>`], or `..` [`RangeFull`]
+    Return the range of converted graphemes for the original grapheme at `index`
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
-    const G: &[&str] = &["a\u{310}", "e\u{301}", "o\u{308}\u{332}"];
+    let (upper, map) = GString::from("\u{df}e\u{301}").convert_case(Casing::Upper);
 
-    let s = GString::from(S);
+    assert_eq!(upper, "SSE\u{301}");
+    assert_eq!(map.get(0), Some(0..2));
+    assert_eq!(upper.slice(map.get(0).unwrap()), "SS");
+    assert_eq!(map.get(1), Some(2..3));
+    assert_eq!(map.get(2), None);
+    ```
+    */
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Range<usize>> {
+        self.ranges.get(index).cloned()
+    }
 
-    assert_eq!(&s[0], G[0]);
-    assert_eq!(&s[1], G[1]);
-    assert_eq!(&s[2], G[2]);
+    /**
+    Return the converted position for an original position
 
-    for start in 0..3 {
-        for stop in 1..4 {
-            if stop > start {
-                assert_eq!(&s[start..stop], G[start..stop].to_vec());
-                assert_eq!(&s[..stop], G[..stop].to_vec());
-            }
+    ```
+    use gstring::*;
+
+    let (upper, map) = GString::from("stra\u{df}e").convert_case(Casing::Upper);
+
+    assert_eq!(upper, "STRASSE");
+    assert_eq!(map.position(4), Some(4));
+    assert_eq!(map.position(5), Some(6));
+    assert_eq!(map.position(6), Some(7));
+    assert_eq!(map.position(7), None);
+    ```
+
+    A position equal to the original length maps to the converted length; no valid position
+    exists for any greater position.
+    */
+    #[must_use]
+    pub fn position(&self, position: usize) -> Option<usize> {
+        match self.ranges.get(position) {
+            Some(r) => Some(r.start),
+            None => (position == self.ranges.len()).then_some(self.len),
         }
-        assert_eq!(&s[start..], G[start..].to_vec());
     }
-    assert_eq!(&s[..], G);
-    ```
 
-    See also the [`GString::slice`] method.
+    /**
+    Return a slice reference to the ranges of converted graphemes, one per original grapheme
 
-    [`RangeFrom<usize>`]: std::ops::RangeFrom
+    ```
+    use gstring::*;
 
-    [`RangeTo<usize>`]: std::ops::RangeTo
+    let mut s = GString::from("a\r\nb\n");
+    let map = s.normalize_line_endings(LineEnding::Lf);
 
-    [`RangeFull`]: std::ops::RangeFull
+    assert_eq!(s, "a\nb\n");
+    assert_eq!(map.ranges(), &[0..1, 1..2, 2..3, 3..4]);
+    ```
     */
-    fn index(&self, index: I) -> &Self::Output {
-        &self.data[index]
+    #[must_use]
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
     }
 }
 
-impl std::cmp::PartialEq<GString> for GString {
+//--------------------------------------------------------------------------------------------------
+
+/**
+Emoji skin tone modifiers (U+1F3FB..U+1F3FF)
+
+```
+use gstring::*;
+
+let g = Grapheme::from("\u{1f44b}\u{1f3ff}").unwrap();
+
+assert_eq!(g.skin_tones(), &[SkinTone::Dark]);
+assert!(SkinTone::Light < SkinTone::Dark);
+```
+
+See [`Grapheme::skin_tones`].
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SkinTone {
+    /// Light skin tone (U+1F3FB), Fitzpatrick type 1-2
+    Light,
+
+    /// Medium-light skin tone (U+1F3FC), Fitzpatrick type 3
+    MediumLight,
+
+    /// Medium skin tone (U+1F3FD), Fitzpatrick type 4
+    Medium,
+
+    /// Medium-dark skin tone (U+1F3FE), Fitzpatrick type 5
+    MediumDark,
+
+    /// Dark skin tone (U+1F3FF), Fitzpatrick type 6
+    Dark,
+}
+
+impl SkinTone {
     /**
-    Compare two [`GString`]s
+    Return the skin tone for a modifier [`char`] or [`None`] if it is not a modifier
+
+    ```
+    use gstring::*;
+
+    assert_eq!(SkinTone::from_char('\u{1f3fb}'), Some(SkinTone::Light));
+    assert_eq!(SkinTone::from_char('\u{1f3fe}'), Some(SkinTone::MediumDark));
+    assert_eq!(SkinTone::from_char('\u{1f44d}'), None);
+    ```
+    */
+    #[must_use]
+    pub fn from_char(c: char) -> Option<SkinTone> {
+        match c {
+            '\u{1f3fb}' => Some(SkinTone::Light),
+            '\u{1f3fc}' => Some(SkinTone::MediumLight),
+            '\u{1f3fd}' => Some(SkinTone::Medium),
+            '\u{1f3fe}' => Some(SkinTone::MediumDark),
+            '\u{1f3ff}' => Some(SkinTone::Dark),
+            _ => None,
+        }
+    }
+
+    /**
+    Return the modifier [`char`]
 
     ```
     use gstring::*;
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+    assert_eq!(SkinTone::Medium.to_char(), '\u{1f3fd}');
+    assert_eq!(SkinTone::from_char(SkinTone::Dark.to_char()), Some(SkinTone::Dark));
+    ```
+    */
+    #[must_use]
+    pub fn to_char(self) -> char {
+        match self {
+            SkinTone::Light => '\u{1f3fb}',
+            SkinTone::MediumLight => '\u{1f3fc}',
+            SkinTone::Medium => '\u{1f3fd}',
+            SkinTone::MediumDark => '\u{1f3fe}',
+            SkinTone::Dark => '\u{1f3ff}',
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Patterns
+
+/**
+Trait for patterns that can be searched for in a [`GString`]
+
+Patterns always match whole graphemes, so `"e"` never matches the first half of `"e\u{301}"`.
+
+| Pattern                         | Matches                                    |
+|---------------------------------|--------------------------------------------|
+| `&`[`GString`]                  | The sequence of graphemes                  |
+| [`&str`], `&`[`String`]         | The sequence of graphemes                  |
+| [`Grapheme`], `&`[`Grapheme`]   | The grapheme                               |
+| [`char`]                        | A grapheme consisting of only the [`char`] |
+| `&[`[`Grapheme`]`]`             | Any of the graphemes                       |
+| `Fn(&`[`Grapheme`]`) -> bool`   | Any grapheme for which it returns [`true`] |
+
+```
+use gstring::*;
+
+let s = GString::from("ae\u{301}e e\u{301}");
+
+assert_eq!(s.rfind("e\u{301}"), Some(4));
+assert_eq!(s.rfind(&GString::from("e")), Some(2));
+assert_eq!(s.rfind(&Grapheme::from("a").unwrap()), Some(0));
+assert_eq!(s.rfind(|g: &Grapheme| g == " "), Some(3));
+assert_eq!(s.rfind('e'), Some(2));
+assert_eq!(s.rfind(&graphemes("ae")[..]), Some(2));
+```
+*/
+pub trait GPattern {
+    /// Searcher for this pattern
+    type Searcher: GSearcher;
+
+    /// Create the searcher for this pattern
+    fn searcher(self) -> Self::Searcher;
+}
+
+/// Trait for searching a slice of graphemes, created by [`GPattern::searcher`]
+pub trait GSearcher {
+    /// Return the range of the first match in the haystack
+    fn find_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>>;
+
+    /// Return the range of the last match in the haystack
+    fn rfind_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>>;
+
+    /// Return the length of the match at the start of the haystack
+    fn prefix_in(&self, haystack: &[Grapheme]) -> Option<usize>;
+
+    /// Return the length of the match at the end of the haystack
+    fn suffix_in(&self, haystack: &[Grapheme]) -> Option<usize>;
+}
+
+impl<'a> GPattern for &'a GString {
+    type Searcher = SequenceSearcher<'a>;
+
+    fn searcher(self) -> Self::Searcher {
+        SequenceSearcher::new(Cow::Borrowed(&self.data))
+    }
+}
+
+impl GPattern for &str {
+    type Searcher = SequenceSearcher<'static>;
+
+    fn searcher(self) -> Self::Searcher {
+        SequenceSearcher::new(Cow::Owned(graphemes(self)))
+    }
+}
+
+impl GPattern for &String {
+    type Searcher = SequenceSearcher<'static>;
+
+    fn searcher(self) -> Self::Searcher {
+        self.as_str().searcher()
+    }
+}
+
+impl<'a> GPattern for &'a Grapheme {
+    type Searcher = SequenceSearcher<'a>;
+
+    fn searcher(self) -> Self::Searcher {
+        SequenceSearcher::new(Cow::Borrowed(std::slice::from_ref(self)))
+    }
+}
+
+impl GPattern for Grapheme {
+    type Searcher = SequenceSearcher<'static>;
+
+    fn searcher(self) -> Self::Searcher {
+        SequenceSearcher::new(Cow::Owned(vec![self]))
+    }
+}
+
+impl GPattern for char {
+    type Searcher = SequenceSearcher<'static>;
+
+    fn searcher(self) -> Self::Searcher {
+        Grapheme {
+            data: self.to_string(),
+        }
+        .searcher()
+    }
+}
+
+impl<'a> GPattern for &'a [Grapheme] {
+    type Searcher = SetSearcher<'a>;
+
+    fn searcher(self) -> Self::Searcher {
+        SetSearcher { set: self }
+    }
+}
+
+impl<F: Fn(&Grapheme) -> bool> GPattern for F {
+    type Searcher = PredicateSearcher<F>;
+
+    fn searcher(self) -> Self::Searcher {
+        PredicateSearcher { predicate: self }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/**
+Boyer-Moore-Horspool search for a sequence of graphemes
+
+The forward skip table maps each grapheme in the needle (except the last) to the distance from its
+last occurrence to the end of the needle.
+The reverse skip table is the mirror image: it maps each grapheme in the needle (except the first)
+to the distance from its first occurrence to the start of the needle.
+*/
+pub struct SequenceSearcher<'a> {
+    needle: Cow<'a, [Grapheme]>,
+    skip: HashMap<String, usize>,
+    rskip: HashMap<String, usize>,
+}
+
+impl<'a> SequenceSearcher<'a> {
+    fn new(needle: Cow<'a, [Grapheme]>) -> SequenceSearcher<'a> {
+        let m = needle.len();
+        let skip = needle
+            .iter()
+            .take(m.saturating_sub(1))
+            .enumerate()
+            .map(|(i, g)| (g.data.clone(), m - 1 - i))
+            .collect();
+        let rskip = needle
+            .iter()
+            .enumerate()
+            .skip(1)
+            .rev()
+            .map(|(i, g)| (g.data.clone(), i))
+            .collect();
+        SequenceSearcher {
+            needle,
+            skip,
+            rskip,
+        }
+    }
+}
+
+impl GSearcher for SequenceSearcher<'_> {
+    fn find_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>> {
+        let needle = &*self.needle;
+        let m = needle.len();
+        if m == 0 {
+            return Some(0..0);
+        }
+        let mut i = 0;
+        while i + m <= haystack.len() {
+            let last = &haystack[i + m - 1];
+            if last == &needle[m - 1] && haystack[i..i + m - 1] == needle[..m - 1] {
+                return Some(i..i + m);
+            }
+            i += self.skip.get(last.as_str()).copied().unwrap_or(m);
+        }
+        None
+    }
+
+    fn rfind_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>> {
+        let needle = &*self.needle;
+        let m = needle.len();
+        let mut i = haystack.len().checked_sub(m)?;
+        if m == 0 {
+            return Some(i..i);
+        }
+        loop {
+            let first = &haystack[i];
+            if first == &needle[0] && haystack[i + 1..i + m] == needle[1..] {
+                return Some(i..i + m);
+            }
+            i = i.checked_sub(self.rskip.get(first.as_str()).copied().unwrap_or(m))?;
+        }
+    }
 
-    let s1 = GString::from(S);
-    let s2 = GString::from(S);
-    let s3 = GString::from(S);
+    fn prefix_in(&self, haystack: &[Grapheme]) -> Option<usize> {
+        haystack
+            .starts_with(&self.needle)
+            .then_some(self.needle.len())
+    }
 
-    assert_eq!(s1, s2);
-    assert_ne!(s3, GString::from(""));
-    ```
-    */
-    fn eq(&self, other: &GString) -> bool {
-        self.data == other.data
+    fn suffix_in(&self, haystack: &[Grapheme]) -> Option<usize> {
+        haystack
+            .ends_with(&self.needle)
+            .then_some(self.needle.len())
     }
 }
 
-impl std::cmp::PartialEq<GString> for &GString {
-    /**
-    Compare a [`GString`] to a `&`[`GString`] (or two `&`[`GString`]s)
+//--------------------------------------------------------------------------------------------------
 
-    ```
-    use gstring::*;
+/// Search for single graphemes in a set
+pub struct SetSearcher<'a> {
+    set: &'a [Grapheme],
+}
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+impl GSearcher for SetSearcher<'_> {
+    fn find_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>> {
+        haystack
+            .iter()
+            .position(|g| self.set.contains(g))
+            .map(|i| i..i + 1)
+    }
 
-    let s1 = GString::from(S);
-    let s2 = GString::from(S);
-    let empty = GString::from("");
+    fn rfind_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>> {
+        haystack
+            .iter()
+            .rposition(|g| self.set.contains(g))
+            .map(|i| i..i + 1)
+    }
 
-    assert_eq!(&s1, s2);
-    assert_ne!(&s1, empty);
+    fn prefix_in(&self, haystack: &[Grapheme]) -> Option<usize> {
+        haystack.first().filter(|g| self.set.contains(g)).map(|_| 1)
+    }
 
-    assert_eq!(&s1, &s2);
-    assert_ne!(&s1, &empty);
-    ```
-    */
-    fn eq(&self, other: &GString) -> bool {
-        self.data == other.data
+    fn suffix_in(&self, haystack: &[Grapheme]) -> Option<usize> {
+        haystack.last().filter(|g| self.set.contains(g)).map(|_| 1)
     }
 }
 
-impl std::cmp::PartialEq<String> for GString {
-    /**
-    Compare a [`GString`] to a [`String`]
+//--------------------------------------------------------------------------------------------------
 
-    ```
-    use gstring::*;
+/// Search for single graphemes matching a predicate
+pub struct PredicateSearcher<F> {
+    predicate: F,
+}
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+impl<F: Fn(&Grapheme) -> bool> GSearcher for PredicateSearcher<F> {
+    fn find_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>> {
+        haystack.iter().position(&self.predicate).map(|i| i..i + 1)
+    }
 
-    let s = GString::from(S);
+    fn rfind_in(&self, haystack: &[Grapheme]) -> Option<Range<usize>> {
+        haystack.iter().rposition(&self.predicate).map(|i| i..i + 1)
+    }
 
-    assert_eq!(s, S.to_string());
-    assert_ne!(s, String::new());
-    ```
-    */
-    fn eq(&self, other: &String) -> bool {
-        self == GString::from(other)
+    fn prefix_in(&self, haystack: &[Grapheme]) -> Option<usize> {
+        haystack.first().filter(|g| (self.predicate)(g)).map(|_| 1)
     }
-}
 
-impl std::cmp::PartialEq<&str> for GString {
-    /**
-    Compare a [`GString`] to a [`&str`]
+    fn suffix_in(&self, haystack: &[Grapheme]) -> Option<usize> {
+        haystack.last().filter(|g| (self.predicate)(g)).map(|_| 1)
+    }
+}
 
-    ```
-    use gstring::*;
+//--------------------------------------------------------------------------------------------------
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+/// Created by [`GString::match_ranges`] and [`GString::match_ranges_overlapping`] to iterate the
+/// ranges of matches of a [`GPattern`]
+pub struct MatchRanges<'a, S> {
+    haystack: &'a [Grapheme],
+    searcher: S,
+    position: usize,
+    overlapping: bool,
+}
 
-    let s = GString::from(S);
+impl<S: GSearcher> Iterator for MatchRanges<'_, S> {
+    type Item = Range<usize>;
 
-    assert_eq!(s, S);
-    assert_ne!(s, "");
-    ```
-    */
-    fn eq(&self, other: &&str) -> bool {
-        self == GString::from(other)
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.position;
+        let r = self.searcher.find_in(self.haystack.get(offset..)?)?;
+        let r = r.start + offset..r.end + offset;
+        self.position = if self.overlapping || r.is_empty() {
+            r.start + 1
+        } else {
+            r.end
+        };
+        Some(r)
     }
 }
 
-impl std::cmp::PartialEq<str> for GString {
-    /**
-    Compare a [`GString`] to a [`str`]
-
-    ```
-    use gstring::*;
+/// Created by [`GString::find_all`] and [`GString::find_all_overlapping`] to iterate the indices
+/// of matches of a [`GPattern`]
+pub struct MatchIndices<'a, S> {
+    ranges: MatchRanges<'a, S>,
+}
 
-    let s = GString::from("a\u{310}e\u{301}o\u{308}\u{332}");
+impl<S: GSearcher> Iterator for MatchIndices<'_, S> {
+    type Item = usize;
 
-    assert_eq!(s, "a\u{310}e\u{301}o\u{308}\u{332}");
-    assert_ne!(s, "");
-    ```
-    */
-    fn eq(&self, other: &str) -> bool {
-        self == GString::from(other)
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ranges.next().map(|r| r.start)
     }
 }
 
 //--------------------------------------------------------------------------------------------------
 
-/// Created by [`GString::iter`] to iterate graphemes by reference
-pub struct GStringRefIter<'a> {
-    gstring: &'a GString,
-    index: usize,
+/// Created by [`GString::split`], [`GString::splitn`] and [`GString::split_terminator`] to iterate
+/// the pieces between matches of a [`GPattern`]
+pub struct Split<'a, S> {
+    matches: MatchRanges<'a, S>,
+    start: usize,
+    remaining: usize,
+    terminator: bool,
+    finished: bool,
+    newline_policy: NewlinePolicy,
 }
 
-impl<'a> Iterator for GStringRefIter<'a> {
-    type Item = &'a Grapheme;
-
-    /**
-    Get the next grapheme by reference
+impl<'a, S: GSearcher> Iterator for Split<'a, S> {
+    type Item = GStr<'a>;
 
-    ```
-    use gstring::*;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let haystack = self.matches.haystack;
+        self.remaining = self.remaining.saturating_sub(1);
+        if self.remaining > 0
+            && let Some(m) = self.matches.next()
+        {
+            let data = &haystack[self.start..m.start];
+            self.start = m.end;
+            return Some(GStr {
+                data,
+                newline_policy: self.newline_policy,
+            });
+        }
+        self.finished = true;
+        let data = &haystack[self.start..];
+        (!(self.terminator && data.is_empty())).then_some(GStr {
+            data,
+            newline_policy: self.newline_policy,
+        })
+    }
+}
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+/// Created by [`GString::rsplit`] to iterate the pieces between matches of a [`GPattern`] in
+/// reverse order
+pub struct RSplit<'a, S> {
+    haystack: &'a [Grapheme],
+    searcher: S,
+    end: usize,
+    search_end: Option<usize>,
+    finished: bool,
+    newline_policy: NewlinePolicy,
+}
 
-    let s = GString::from(S);
-    let mut i = s.iter();
+impl<'a, S: GSearcher> Iterator for RSplit<'a, S> {
+    type Item = GStr<'a>;
 
-    assert_eq!(i.next().unwrap(), "a\u{310}");
-    assert_eq!(i.next().unwrap(), "e\u{301}");
-    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
-    assert_eq!(i.next(), None);
-    ```
-    */
     fn next(&mut self) -> Option<Self::Item> {
-        let r = self.gstring.data.get(self.index);
-        self.index += 1;
-        r
+        if self.finished {
+            return None;
+        }
+        if let Some(m) = self
+            .search_end
+            .and_then(|n| self.searcher.rfind_in(&self.haystack[..n]))
+        {
+            let data = &self.haystack[m.end..self.end];
+            self.end = m.start;
+            self.search_end = if m.is_empty() {
+                m.start.checked_sub(1)
+            } else {
+                Some(m.start)
+            };
+            return Some(GStr {
+                data,
+                newline_policy: self.newline_policy,
+            });
+        }
+        self.finished = true;
+        Some(GStr {
+            data: &self.haystack[..self.end],
+            newline_policy: self.newline_policy,
+        })
     }
 }
 
-//--------------------------------------------------------------------------------------------------
-
-/// Created by [`GString::into_iter`] to iterate graphemes
-pub struct GStringIter {
-    gstring: GString,
-    index: usize,
+/// Created by [`GString::split_whitespace`] to iterate the pieces between whitespace graphemes
+pub struct SplitWhitespace<'a> {
+    data: &'a [Grapheme],
+    newline_policy: NewlinePolicy,
 }
 
-impl Iterator for GStringIter {
-    type Item = Grapheme;
-
-    /**
-    Get the next grapheme
+impl<'a> Iterator for SplitWhitespace<'a> {
+    type Item = GStr<'a>;
 
-    ```
-    use gstring::*;
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.data.iter().position(|g| !g.is_whitespace())?;
+        let data = &self.data[start..];
+        let end = data
+            .iter()
+            .position(Grapheme::is_whitespace)
+            .unwrap_or(data.len());
+        self.data = &data[end..];
+        Some(GStr {
+            data: &data[..end],
+            newline_policy: self.newline_policy,
+        })
+    }
+}
 
-    const S: &str = "a\u{310}e\u{301}o\u{308}\u{332}";
+/// Created by [`GString::lines_without_endings`] to iterate lines without their newline graphemes
+pub struct LinesWithoutEndings<'a> {
+    data: &'a [Grapheme],
+    newline_policy: NewlinePolicy,
+}
 
-    let mut i = GString::from(S).into_iter();
+impl<'a> Iterator for LinesWithoutEndings<'a> {
+    type Item = GStr<'a>;
 
-    assert_eq!(i.next().unwrap(), "a\u{310}");
-    assert_eq!(i.next().unwrap(), "e\u{301}");
-    assert_eq!(i.next().unwrap(), "o\u{308}\u{332}");
-    assert_eq!(i.next(), None);
-    ```
-    */
     fn next(&mut self) -> Option<Self::Item> {
-        let r = self.gstring.data.get(self.index).cloned();
-        self.index += 1;
-        r
+        if self.data.is_empty() {
+            return None;
+        }
+        let data = self.data;
+        let end = data
+            .iter()
+            .position(|g| g.is_newline_with(self.newline_policy))
+            .unwrap_or(data.len());
+        self.data = data.get(end + 1..).unwrap_or_default();
+        Some(GStr {
+            data: &data[..end],
+            newline_policy: self.newline_policy,
+        })
     }
 }
 
@@ -1431,7 +6742,7 @@ impl GStringTrait for String {
 impl GStringTrait for &str {
     /// Create a new [`GString`] from a [`&str`]
     fn gstring(&self) -> GString {
-        GString::from(self)
+        GString::from(*self)
     }
 
     /// Create a new [`Vec`] of graphemes from a [`&str`]
@@ -1447,24 +6758,81 @@ impl GStringTrait for &str {
 
 //--------------------------------------------------------------------------------------------------
 
-/// Trait providing the `is_newline` method
+/**
+Trait providing the `is_newline` and `is_newline_with` methods
+
+```
+use gstring::*;
+
+struct Eol;
+
+impl IsNewline for Eol {
+    fn is_newline(&self) -> bool {
+        true
+    }
+}
+
+assert!(Eol.is_newline());
+assert!(Eol.is_newline_with(NewlinePolicy::Unicode));
+```
+*/
 pub trait IsNewline {
-    /// Returns true if it is a newline grapheme
+    /// Returns true if it is a newline grapheme according to the default [`NewlinePolicy`]
     fn is_newline(&self) -> bool;
+
+    /**
+    Returns true if it is a newline grapheme according to a [`NewlinePolicy`]
+
+    The default implementation returns [`IsNewline::is_newline`] for every policy, so types that
+    only implement `is_newline` keep working; override it to recognize the other line terminators.
+    */
+    fn is_newline_with(&self, newline_policy: NewlinePolicy) -> bool {
+        let _ = newline_policy;
+        self.is_newline()
+    }
 }
 
 impl IsNewline for str {
-    /// Implemente the `is_newline` method for [`str`]
+    /// Implement the `is_newline` method for [`str`] with the default [`NewlinePolicy`]
     fn is_newline(&self) -> bool {
-        ["\n", "\r\n"].contains(&self)
+        self.is_newline_with(NewlinePolicy::default())
+    }
+
+    /**
+    Implement the `is_newline_with` method for [`str`]
+
+    ```
+    use gstring::*;
+
+    assert!("\r\n".is_newline());
+    assert!(!"\r".is_newline());
+    assert!("\r".is_newline_with(NewlinePolicy::LfCrlfCr));
+    assert!(!"\u{2028}".is_newline_with(NewlinePolicy::LfCrlfCr));
+    assert!("\u{2028}".is_newline_with(NewlinePolicy::Unicode));
+    ```
+    */
+    fn is_newline_with(&self, newline_policy: NewlinePolicy) -> bool {
+        match newline_policy {
+            NewlinePolicy::LfCrlf => ["\n", "\r\n"].contains(&self),
+            NewlinePolicy::LfCrlfCr => ["\n", "\r\n", "\r"].contains(&self),
+            NewlinePolicy::Unicode => [
+                "\n", "\r\n", "\r", "\u{b}", "\u{c}", "\u{85}", "\u{2028}", "\u{2029}",
+            ]
+            .contains(&self),
+        }
     }
 }
 
 impl IsNewline for Grapheme {
-    /// Implemente the `is_newline` method for [`Grapheme`]
+    /// Implement the `is_newline` method for [`Grapheme`] with the default [`NewlinePolicy`]
     fn is_newline(&self) -> bool {
         self.data.is_newline()
     }
+
+    /// Implement the `is_newline_with` method for [`Grapheme`]
+    fn is_newline_with(&self, newline_policy: NewlinePolicy) -> bool {
+        self.data.is_newline_with(newline_policy)
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -1497,29 +6865,29 @@ pub fn graphemes(s: &str) -> Vec<Grapheme> {
 // Helper functions
 
 /// Return the indices of all newline graphemes
-fn newline_indices(data: &[Grapheme]) -> Vec<usize> {
+fn newline_indices(data: &[Grapheme], newline_policy: NewlinePolicy) -> Vec<usize> {
     data.iter()
         .enumerate()
-        .filter(|(_, g)| g.is_newline())
+        .filter(|(_, g)| g.is_newline_with(newline_policy))
         .map(|(i, _)| i)
         .collect()
 }
 
 /// Calculate the "shape" of the [`GString`] content
-fn calc_shape(data: &[Grapheme]) -> Vec<usize> {
-    lines(data)
+fn calc_shape(data: &[Grapheme], newline_policy: NewlinePolicy) -> Vec<usize> {
+    lines(data, newline_policy)
         .iter()
         .map(|line| line.len().saturating_sub(1))
         .collect()
 }
 
 /// Split graphemes into lines as a [`Vec`] of [`GString`]s
-fn lines(data: &[Grapheme]) -> Vec<GString> {
+fn lines(data: &[Grapheme], newline_policy: NewlinePolicy) -> Vec<GString> {
     let mut r = vec![];
     let mut t = vec![];
     for g in data {
         t.push(g.clone());
-        if g.is_newline() {
+        if g.is_newline_with(newline_policy) {
             r.push(std::mem::take(&mut t));
         }
     }
@@ -1527,11 +6895,326 @@ fn lines(data: &[Grapheme]) -> Vec<GString> {
     r.into_iter()
         .map(|data| {
             let shape = vec![data.len().saturating_sub(1)];
-            GString { data, shape }
+            GString {
+                data,
+                shape,
+                newline_policy,
+            }
         })
         .collect()
 }
 
+/// Replace sorted non-overlapping ranges of graphemes with a [`&str`]
+fn replace_ranges(
+    data: &[Grapheme],
+    ranges: impl IntoIterator<Item = Range<usize>>,
+    replace_with: &str,
+) -> Vec<Grapheme> {
+    let mut r = String::new();
+    let mut position = 0;
+    for range in ranges {
+        r.extend(data[position..range.start].iter().map(Grapheme::as_str));
+        r.push_str(replace_with);
+        position = range.end;
+    }
+    r.extend(data[position..].iter().map(Grapheme::as_str));
+    graphemes(&r)
+}
+
+/// Return the [`char`]s of the graphemes, which [`GString`] and [`GStr`] compare by
+fn chars(data: &[Grapheme]) -> impl Iterator<Item = char> + '_ {
+    data.iter().flat_map(|g| g.data.chars())
+}
+
+/// Hash the [`char`]s of the graphemes independently of how they are segmented
+fn hash_chars<H: std::hash::Hasher>(data: &[Grapheme], state: &mut H) {
+    use std::hash::Hash;
+    state.write_usize(chars(data).count());
+    for c in chars(data) {
+        c.hash(state);
+    }
+}
+
+/**
+Re-segment the graphemes around a range so that clusters spanning its edges are merged
+
+The window includes the grapheme before the range and extends past the range until a grapheme
+after it is unchanged (or the end is reached), since merging can cascade (e.g. regional indicator
+pairs).
+
+Returns where the changed graphemes end before and after re-segmenting, not counting the unchanged
+graphemes at the end of the window.
+*/
+fn resegment(data: &mut Vec<Grapheme>, range: Range<usize>) -> (usize, usize) {
+    let a = range.start.saturating_sub(1);
+    let mut b = (range.end + 1).min(data.len());
+    let new = loop {
+        let joined = data[a..b].iter().map(Grapheme::as_str).collect::<String>();
+        let new = graphemes(&joined);
+        if b == data.len() || (b > range.end && new.last() == Some(&data[b - 1])) {
+            break new;
+        }
+        b += 1;
+    };
+    let unchanged = new
+        .iter()
+        .rev()
+        .zip(data[range.end..b].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let new_end = a + new.len() - unchanged;
+    data.splice(a..b, new);
+    (b - unchanged, new_end)
+}
+
+/// Return the byte offset of each grapheme plus the total byte length
+fn byte_offsets(data: &[Grapheme]) -> Vec<usize> {
+    let mut r = Vec::with_capacity(data.len() + 1);
+    let mut offset = 0;
+    r.push(offset);
+    for g in data {
+        offset += g.data.len();
+        r.push(offset);
+    }
+    r
+}
+
+/// Convert a byte range to a grapheme index range, snapping outward to grapheme boundaries
+#[cfg(feature = "regex")]
+fn grapheme_range(offsets: &[usize], range: &Range<usize>) -> Range<usize> {
+    offsets.partition_point(|&o| o <= range.start) - 1..offsets.partition_point(|&o| o < range.end)
+}
+
+/**
+Find up to `limit` non-overlapping regex matches that start and end on grapheme boundaries
+
+Rejected matches restart the search at the grapheme boundary after the start of the match.
+*/
+#[cfg(feature = "regex")]
+fn regex_captures<'s>(
+    regex: &Regex,
+    s: &'s str,
+    offsets: &[usize],
+    limit: usize,
+) -> Vec<Captures<'s>> {
+    let next_boundary = |b: usize| {
+        offsets
+            .get(offsets.partition_point(|&o| o <= b))
+            .copied()
+            .unwrap_or(usize::MAX)
+    };
+    let mut r = vec![];
+    let mut start = 0;
+    let mut last_end = None;
+    while r.len() < limit && start <= s.len() {
+        let Some(c) = regex.captures_at(s, start) else {
+            break;
+        };
+        let m = c.get(0).unwrap();
+        if offsets.binary_search(&m.start()).is_err()
+            || offsets.binary_search(&m.end()).is_err()
+            || (m.is_empty() && last_end == Some(m.end()))
+        {
+            start = next_boundary(m.start());
+        } else {
+            start = if m.is_empty() {
+                next_boundary(m.end())
+            } else {
+                m.end()
+            };
+            last_end = Some(m.end());
+            r.push(c);
+        }
+    }
+    r
+}
+
+/// Transform the haystack and needle into comparable graphemes per [`FindOptions`]
+fn search_keys(
+    data: &[Grapheme],
+    pattern: &str,
+    options: FindOptions,
+) -> (Vec<Grapheme>, Vec<Grapheme>) {
+    let key = |g: &Grapheme| {
+        let mut data = g.data.clone();
+        if options.diacritic_insensitive {
+            data = data.nfd().filter(|c| !is_combining_mark(*c)).collect();
+        } else if options.canonical {
+            data = data.nfd().collect();
+        }
+        if options.case_insensitive {
+            data = fold_case(&data);
+        }
+        Grapheme { data }
+    };
+    (
+        data.iter().map(key).collect(),
+        graphemes(pattern).iter().map(key).collect(),
+    )
+}
+
+/**
+Full case folding (the `C` and `F` mappings of the Unicode `CaseFolding.txt`)
+
+Each [`char`] is lowercased and then mapped through its uppercase back to lowercase, which folds
+characters like `ß`, `ẞ`, `ς`, `ſ` and `ﬁ` together with their other case forms.
+Cherokee folds to uppercase and the dotless `ı` has no folding.
+*/
+fn fold_case(s: &str) -> String {
+    let mut r = String::new();
+    for c in s.chars().flat_map(char::to_lowercase) {
+        if c == 'ı' {
+            r.push(c);
+            continue;
+        }
+        for u in c.to_uppercase() {
+            if ('Ꭰ'..='Ᏽ').contains(&u) {
+                r.push(u);
+            } else {
+                r.extend(u.to_lowercase());
+            }
+        }
+    }
+    r
+}
+
+/// Convert a grapheme to title case: the first [`char`] to title case and the rest to lowercase
+fn titlecase(s: &str) -> String {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    let mut r = match first {
+        'Ǆ' | 'ǅ' | 'ǆ' => String::from("ǅ"),
+        'Ǉ' | 'ǈ' | 'ǉ' => String::from("ǈ"),
+        'Ǌ' | 'ǋ' | 'ǌ' => String::from("ǋ"),
+        'Ǳ' | 'ǲ' | 'ǳ' => String::from("ǲ"),
+        'ß' => String::from("Ss"),
+        c => c.to_uppercase().collect(),
+    };
+    r.push_str(&chars.as_str().to_lowercase());
+    r
+}
+
+/// Return [`true`] if the grapheme at `index` is a capital sigma at the end of a word
+fn is_final_sigma(data: &[Grapheme], index: usize) -> bool {
+    let is_letter = |g: &Grapheme| g.data.chars().next().is_some_and(char::is_alphabetic);
+    data[index].data.starts_with('Σ')
+        && index > 0
+        && is_letter(&data[index - 1])
+        && !data.get(index + 1).is_some_and(is_letter)
+}
+
+/**
+Split graphemes into identifier words
+
+Words are the Unicode word segments containing alphanumeric or emoji graphemes, split at any other
+graphemes (like punctuation) and at case transitions (`aB` and the `B` in `ABc`).
+*/
+fn identifier_words(data: &[Grapheme]) -> Vec<Vec<Grapheme>> {
+    let base = |g: &Grapheme| g.data.chars().next().unwrap_or_default();
+    let is_word = |g: &Grapheme| base(g).is_alphanumeric() || is_emoji(&g.data);
+    let s = data.iter().map(Grapheme::as_str).collect::<String>();
+    let offsets = byte_offsets(data);
+    let mut r = vec![];
+    for (b, segment) in s.split_word_bound_indices() {
+        let start = offsets.partition_point(|&o| o < b);
+        let end = offsets.partition_point(|&o| o < b + segment.len());
+        let segment = &data[start..end];
+        let mut word: Vec<Grapheme> = vec![];
+        for (i, g) in segment.iter().enumerate() {
+            if !is_word(g) {
+                if !word.is_empty() {
+                    r.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+            if let Some(prev) = word.last() {
+                let (prev, this) = (base(prev), base(g));
+                let next = segment.get(i + 1).map(base).unwrap_or_default();
+                if ((prev.is_lowercase() || prev.is_numeric()) && this.is_uppercase())
+                    || (prev.is_uppercase() && this.is_uppercase() && next.is_lowercase())
+                {
+                    r.push(std::mem::take(&mut word));
+                }
+            }
+            word.push(g.clone());
+        }
+        if !word.is_empty() {
+            r.push(word);
+        }
+    }
+    r
+}
+
+/// Return [`true`] if a grapheme is an emoji
+fn is_emoji(s: &str) -> bool {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    is_regional_indicator(first)
+        || matches!(
+            first.emoji_status(),
+            EmojiStatus::EmojiPresentation
+                | EmojiStatus::EmojiPresentationAndModifierBase
+                | EmojiStatus::EmojiPresentationAndEmojiComponent
+                | EmojiStatus::EmojiPresentationAndModifierAndEmojiComponent
+        )
+        || (first.is_emoji_char()
+            && chars.any(|c| ['\u{fe0f}', '\u{20e3}', '\u{200d}'].contains(&c)))
+}
+
+/// Return whether each grapheme is the first grapheme of a word
+fn word_starts(data: &[Grapheme]) -> Vec<bool> {
+    let s = data.iter().map(Grapheme::as_str).collect::<String>();
+    let offsets = byte_offsets(data);
+    let mut r = vec![false; data.len()];
+    for (b, word) in s.split_word_bound_indices() {
+        if word.chars().any(char::is_alphanumeric)
+            && let Ok(i) = offsets.binary_search(&b)
+        {
+            r[i] = true;
+        }
+    }
+    r
+}
+
+/// Resolve a [`RangeBounds<usize>`] to a [`Range<usize>`] for a given length
+fn bounds<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+    let start = match range.start_bound() {
+        Included(&n) => n,
+        Excluded(&n) => n + 1,
+        Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Included(&n) => n + 1,
+        Excluded(&n) => n,
+        Unbounded => len,
+    };
+    start..end
+}
+
+/// Return the next number of the `SplitMix64` pseudorandom number generator
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/**
+Move a position at or after an edit to the edited [`GString`]
+
+`old` and `new` are where the graphemes changed by the edits so far end before and after the edits;
+positions before `old` are inside merged graphemes and move to `new`.
+*/
+fn shift(position: usize, old: usize, new: usize) -> usize {
+    new + position.saturating_sub(old)
+}
+
 /// Find the number of base 10 digits in a number
 fn n_digits(number: usize) -> usize {
     format!("{number}").len()